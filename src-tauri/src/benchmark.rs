@@ -0,0 +1,95 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Per-part `duration_ms` values reported by a single solution run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PartDurations {
+    pub part_one: Option<f64>,
+    pub part_two: Option<f64>,
+}
+
+impl PartDurations {
+    /// Read the durations from a materialized solution state
+    pub fn from_state(state: &serde_json::Value) -> Self {
+        let duration = |part: &str| {
+            state
+                .get(part)
+                .and_then(|p| p.get("duration_ms"))
+                .and_then(|d| d.as_f64())
+        };
+
+        PartDurations {
+            part_one: duration("part_one"),
+            part_two: duration("part_two"),
+        }
+    }
+}
+
+/// Summary statistics for one part across all measured runs
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PartStats {
+    pub samples: usize,
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    pub p95: f64,
+}
+
+impl PartStats {
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        } else {
+            sorted[n / 2]
+        };
+        // Sample standard deviation, as the runs are a sample of possible timings
+        let stddev = if n > 1 {
+            (sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+        // Nearest-rank percentile
+        let p95 = sorted[((0.95 * n as f64).ceil() as usize).clamp(1, n) - 1];
+
+        Some(PartStats {
+            samples: n,
+            min: sorted[0],
+            median,
+            mean,
+            stddev,
+            p95,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BenchmarkSummary {
+    pub iterations: u32,
+    pub warmups: u32,
+    pub part_one: Option<PartStats>,
+    pub part_two: Option<PartStats>,
+}
+
+impl BenchmarkSummary {
+    pub fn from_runs(iterations: u32, warmups: u32, runs: &[PartDurations]) -> Self {
+        let part_one: Vec<f64> = runs.iter().filter_map(|r| r.part_one).collect();
+        let part_two: Vec<f64> = runs.iter().filter_map(|r| r.part_two).collect();
+
+        BenchmarkSummary {
+            iterations,
+            warmups,
+            part_one: PartStats::from_samples(&part_one),
+            part_two: PartStats::from_samples(&part_two),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Writes the TypeScript definitions of the types shared with the frontend.
+//! Usage: generate-bindings [output path]
+
+fn main() -> std::io::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "../src/lib/bindings.ts".to_string());
+    std::fs::write(
+        &path,
+        santa_lang_workbench_lib::bindings::typescript_definitions(),
+    )
+}
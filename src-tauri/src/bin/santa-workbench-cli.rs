@@ -0,0 +1,6 @@
+//! Runs and tests solutions without the workbench window, for CI and
+//! pre-commit hooks. Run with `--help` for usage.
+
+fn main() -> std::process::ExitCode {
+    santa_lang_workbench_lib::cli::main()
+}
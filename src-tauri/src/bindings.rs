@@ -0,0 +1,207 @@
+//! TypeScript definitions for every type crossing the IPC boundary: the
+//! execution and REPL event protocols, execution options and snapshots, the
+//! matrix result, execution history, settings, watch re-runs and test report
+//! formats. They are generated from the JSON
+//! Schema of the Rust types so `src/lib/bindings.ts` cannot drift from the
+//! backend. Regenerate with `make bindings`.
+//!
+//! The schema is rendered here rather than with ts-rs or specta because
+//! schemars already reproduces serde's tagging, flattening and renaming
+//! exactly, and these types only use the handful of schema constructs below.
+
+use crate::commands::execution::ExecutionOptions;
+use crate::commands::watch::WatchRun;
+use crate::history::{HistoryFilter, HistoryRecord};
+use crate::matrix::MatrixResult;
+use crate::protocol::{ExecutionEvent, PROTOCOL_VERSION, ReplEvent};
+use crate::snapshot::ExecutionSnapshot;
+use crate::state::Settings;
+use crate::test_report::ReportFormat;
+use serde_json::{Map, Value};
+
+const HEADER: &str = "// This file is generated by `make bindings` from the Rust types in src-tauri/src.\n// Do not edit it by hand.\n";
+
+pub fn typescript_definitions() -> String {
+    // Types referenced by a root, such as `ExecutionLimits` and
+    // `ConsoleLimits`, are declared with its definitions
+    let roots = [
+        ("ExecutionEvent", schemars::schema_for!(ExecutionEvent)),
+        ("ReplEvent", schemars::schema_for!(ReplEvent)),
+        ("MatrixResult", schemars::schema_for!(MatrixResult)),
+        ("HistoryRecord", schemars::schema_for!(HistoryRecord)),
+        ("HistoryFilter", schemars::schema_for!(HistoryFilter)),
+        ("ExecutionOptions", schemars::schema_for!(ExecutionOptions)),
+        (
+            "ExecutionSnapshot",
+            schemars::schema_for!(ExecutionSnapshot),
+        ),
+        ("Settings", schemars::schema_for!(Settings)),
+        ("WatchRun", schemars::schema_for!(WatchRun)),
+        ("ReportFormat", schemars::schema_for!(ReportFormat)),
+    ];
+
+    let mut out = String::from(HEADER);
+    out.push_str(&format!(
+        "\nexport const PROTOCOL_VERSION = {};\n",
+        PROTOCOL_VERSION
+    ));
+
+    // Types shared between the roots are declared once
+    let mut definitions = Map::new();
+    for (name, schema) in roots {
+        let schema = serde_json::to_value(schema).expect("protocol schema is serializable");
+        out.push_str(&declaration(name, &schema));
+        if let Some(root_definitions) = schema.get("definitions").and_then(Value::as_object) {
+            definitions.extend(root_definitions.clone());
+        }
+    }
+    for (name, definition) in &definitions {
+        out.push_str(&declaration(name, definition));
+    }
+
+    out
+}
+
+/// Render a named schema as an `interface` when it is a plain object,
+/// otherwise as a `type` alias
+fn declaration(name: &str, schema: &Value) -> String {
+    let mut out = String::from("\n");
+    out.push_str(&doc_comment(schema, ""));
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let is_union = schema.get("oneOf").is_some() || schema.get("anyOf").is_some();
+
+    match properties {
+        Some(properties) if !is_union => {
+            out.push_str(&format!("export interface {} {{\n", name));
+            for (field, property) in properties {
+                out.push_str(&doc_comment(property, "  "));
+                out.push_str(&format!("  {}: {};\n", field, ts_type(property)));
+            }
+            out.push_str("}\n");
+        }
+        _ => {
+            let members = schema
+                .get("oneOf")
+                .and_then(Value::as_array)
+                .map(|variants| {
+                    variants
+                        .iter()
+                        .map(|variant| format!("\n  | {}", ts_type(variant)))
+                        .collect::<String>()
+                });
+
+            match (properties, members) {
+                (Some(_), Some(members)) => out.push_str(&format!(
+                    "export type {} = {} & ({}\n);\n",
+                    name,
+                    object_literal(schema),
+                    members
+                )),
+                (None, Some(members)) => {
+                    out.push_str(&format!("export type {} ={};\n", name, members))
+                }
+                _ => out.push_str(&format!("export type {} = {};\n", name, ts_type(schema))),
+            }
+        }
+    }
+
+    out
+}
+
+fn doc_comment(schema: &Value, indent: &str) -> String {
+    match schema.get("description").and_then(Value::as_str) {
+        Some(description) => format!("{}/** {} */\n", indent, description.replace('\n', " ")),
+        None => String::new(),
+    }
+}
+
+fn ts_type(schema: &Value) -> String {
+    if schema == &Value::Bool(true) {
+        return "unknown".to_string();
+    }
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+
+    for combinator in ["allOf", "anyOf", "oneOf"] {
+        if schema.get("properties").is_none()
+            && let Some(members) = schema.get(combinator).and_then(Value::as_array)
+        {
+            let separator = if combinator == "allOf" { " & " } else { " | " };
+            return members
+                .iter()
+                .map(ts_type)
+                .collect::<Vec<_>>()
+                .join(separator);
+        }
+    }
+
+    match schema.get("type") {
+        Some(Value::String(instance_type)) => instance_ts_type(instance_type, schema),
+        Some(Value::Array(instance_types)) => instance_types
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|instance_type| instance_ts_type(instance_type, schema))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn instance_ts_type(instance_type: &str, schema: &Value) -> String {
+    match instance_type {
+        "string" => "string".to_string(),
+        "integer" | "number" => "number".to_string(),
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        "array" => {
+            let items = schema
+                .get("items")
+                .map(ts_type)
+                .unwrap_or("unknown".to_string());
+            if items.contains(' ') {
+                format!("({})[]", items)
+            } else {
+                format!("{}[]", items)
+            }
+        }
+        "object" if schema.get("properties").is_some() => object_literal(schema),
+        "object" => {
+            let values = schema
+                .get("additionalProperties")
+                .map(ts_type)
+                .unwrap_or("unknown".to_string());
+            format!("Record<string, {}>", values)
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+fn object_literal(schema: &Value) -> String {
+    let fields = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(field, property)| format!("{}: {}", field, ts_type(property)))
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+        .unwrap_or_default();
+    format!("{{ {} }}", fields)
+}
@@ -0,0 +1,696 @@
+//! Headless runner for CI and pre-commit hooks. It uses the app's config and
+//! reindeer registry and runs solutions through the same execution pipeline,
+//! so results match what the workbench shows.
+
+use crate::commands::execution::{ExecutionOptions, execute, terminate_all_executions};
+use crate::commands::matrix;
+use crate::host::ExecutionHost;
+use crate::input::ExecutionInput;
+use crate::matrix::{MatrixResult, PartConsensus};
+use crate::process::{self, CancelReason, LimitExceeded};
+use crate::protocol::ExecutionEvent;
+use crate::scheduler::ExecutionScheduler;
+use crate::state::{AppState, Reindeer};
+use crate::test_report::{self, ReportFormat, TestSuite};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::ipc::{Channel, InvokeResponseBody};
+
+const USAGE: &str = "\
+Usage: santa-workbench-cli <command> [options]
+
+Commands:
+  run <file>      Run a solution
+  test <file>     Run a solution's tests, failing if any test fails
+  compare <file>  Run a solution on several reindeer, failing if their answers differ
+  list            List the registered reindeer
+
+Options:
+  -r, --reindeer <id>  Reindeer to use, by ID or codename (repeatable). Defaults
+                       to the default reindeer, or every reindeer for compare
+  -i, --input <file>   Puzzle input to run against
+  -s, --slow           Include tests marked @slow
+      --json           Print results as JSON
+      --junit <file>   Also write test results as JUnit XML
+      --tap <file>     Also write test results as TAP
+      --force          Ignore the result cache
+  -h, --help           Show this help";
+
+/// Failed runs, failing tests and disagreeing answers
+const EXIT_FAILED: u8 = 1;
+/// Bad arguments, config or reindeer
+const EXIT_USAGE: u8 = 2;
+/// Stopped by Ctrl-C
+const EXIT_INTERRUPTED: u8 = 130;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CliCommand {
+    Run,
+    Test,
+    Compare,
+    List,
+}
+
+#[derive(Debug)]
+struct Invocation {
+    command: CliCommand,
+    file: Option<PathBuf>,
+    reindeer: Vec<String>,
+    input: Option<String>,
+    slow: bool,
+    json: bool,
+    force: bool,
+    /// Test reports to write once tests have run
+    reports: Vec<(ReportFormat, PathBuf)>,
+}
+
+impl Invocation {
+    fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let mut args = args.iter();
+        let command = match args.next().map(String::as_str) {
+            None | Some("-h" | "--help" | "help") => return Ok(None),
+            Some("run") => CliCommand::Run,
+            Some("test") => CliCommand::Test,
+            Some("compare") => CliCommand::Compare,
+            Some("list") => CliCommand::List,
+            Some(other) => return Err(format!("Unknown command '{}'", other)),
+        };
+
+        let mut invocation = Invocation {
+            command,
+            file: None,
+            reindeer: Vec::new(),
+            input: None,
+            slow: false,
+            json: false,
+            force: false,
+            reports: Vec::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} needs a value", flag))
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-r" | "--reindeer" => invocation.reindeer.push(value(arg)?),
+                "-i" | "--input" => invocation.input = Some(value(arg)?),
+                "-s" | "--slow" => invocation.slow = true,
+                "--json" => invocation.json = true,
+                "--force" => invocation.force = true,
+                "--junit" => invocation
+                    .reports
+                    .push((ReportFormat::Junit, PathBuf::from(value(arg)?))),
+                "--tap" => invocation
+                    .reports
+                    .push((ReportFormat::Tap, PathBuf::from(value(arg)?))),
+                flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+                file if invocation.file.is_none() => invocation.file = Some(PathBuf::from(file)),
+                extra => return Err(format!("Unexpected argument '{}'", extra)),
+            }
+        }
+
+        if command != CliCommand::List && invocation.file.is_none() {
+            return Err("No solution file given".to_string());
+        }
+        if command != CliCommand::Test && !invocation.reports.is_empty() {
+            return Err("Test reports can only be written by the test command".to_string());
+        }
+
+        Ok(Some(invocation))
+    }
+}
+
+/// Runs executions against the loaded config without a window. The config
+/// is never saved, so the app's own changes to it are not overwritten.
+#[derive(Clone)]
+struct CliHost(Arc<(Mutex<AppState>, ExecutionScheduler)>);
+
+impl ExecutionHost for CliHost {
+    fn state(&self) -> &Mutex<AppState> {
+        &self.0.0
+    }
+
+    fn scheduler(&self) -> &ExecutionScheduler {
+        &self.0.1
+    }
+
+    fn save_state(&self, _state: &AppState) {}
+}
+
+pub fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let invocation = match Invocation::parse(&args) {
+        Ok(Some(invocation)) => invocation,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let state = match AppState::load_headless() {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("error: Failed to load workbench config: {}", e);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    let host = CliHost(Arc::new((Mutex::new(state), ExecutionScheduler::default())));
+
+    // Reindeer run in their own process groups, so Ctrl-C does not reach them
+    // and they are stopped here instead
+    let outcome = tauri::async_runtime::block_on(async {
+        tokio::select! {
+            outcome = run(&host, &invocation) => Some(outcome),
+            _ = tokio::signal::ctrl_c() => None,
+        }
+    });
+
+    match outcome {
+        Some(Ok(true)) => ExitCode::SUCCESS,
+        Some(Ok(false)) => ExitCode::from(EXIT_FAILED),
+        Some(Err(e)) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(EXIT_USAGE)
+        }
+        None => {
+            terminate_all_executions(host.state());
+            ExitCode::from(EXIT_INTERRUPTED)
+        }
+    }
+}
+
+/// Carry out the command, returning whether everything passed
+async fn run(host: &CliHost, invocation: &Invocation) -> Result<bool, String> {
+    if invocation.command == CliCommand::List {
+        list_reindeer(host, invocation.json);
+        return Ok(true);
+    }
+
+    let file = invocation.file.as_deref().ok_or("No solution file given")?;
+    let source = std::fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let reindeer = select_reindeer(host, invocation)?;
+    // Paths are given relative to where the CLI runs, but input files resolve
+    // against the solution's directory
+    let absolute = |path: &Path| {
+        std::path::absolute(path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .to_string_lossy()
+            .to_string()
+    };
+    let options = ExecutionOptions {
+        file_path: Some(absolute(file)),
+        input: invocation
+            .input
+            .as_deref()
+            .map(|path| ExecutionInput::File {
+                path: absolute(Path::new(path)),
+            }),
+        force: invocation.force,
+        ..Default::default()
+    };
+    let working_dir = solution_dir(file);
+    let on_event = console_channel(&reindeer);
+
+    if invocation.command == CliCommand::Compare {
+        let result = matrix::compare(
+            host,
+            format!("matrix_{}", timestamp()),
+            reindeer
+                .iter()
+                .map(|reindeer| reindeer.id.clone())
+                .collect(),
+            source,
+            working_dir,
+            on_event,
+            Some(options),
+        )
+        .await?;
+        return Ok(report_comparison(&result, &reindeer, invocation.json));
+    }
+
+    let mode = match invocation.command {
+        CliCommand::Test if invocation.slow => "test-slow",
+        CliCommand::Test => "test",
+        _ => "run",
+    };
+
+    let mut runs = Vec::with_capacity(reindeer.len());
+    for reindeer in &reindeer {
+        let execution_id = execution_id(reindeer);
+        let outcome = execute(
+            host,
+            execution_id.clone(),
+            reindeer.id.clone(),
+            source.clone(),
+            mode.to_string(),
+            working_dir.clone(),
+            on_event.clone(),
+            Some(options.clone()),
+        )
+        .await;
+
+        let snapshot = host
+            .state()
+            .lock()
+            .map_err(|e| e.to_string())?
+            .execution_snapshots
+            .remove(&execution_id);
+        let snapshot = snapshot.unwrap_or_default();
+
+        runs.push(CliRun {
+            reindeer: reindeer.clone(),
+            exit_code: snapshot.exit_code,
+            signal: snapshot.signal,
+            limit: snapshot.limit,
+            cancelled: snapshot.cancelled,
+            duration_ms: snapshot.duration_ms,
+            error: outcome.err(),
+            result: snapshot.result,
+        });
+    }
+
+    let passed = runs.iter().all(CliRun::passed);
+    if invocation.json {
+        print_json(&runs);
+    } else {
+        for run in &runs {
+            report_run(run);
+        }
+    }
+    write_reports(&invocation.reports, &runs)?;
+    Ok(passed)
+}
+
+/// One reindeer's run of the solution
+#[derive(Serialize)]
+struct CliRun {
+    reindeer: Reindeer,
+    exit_code: Option<i32>,
+    /// Signal that terminated the reindeer (Unix only)
+    signal: Option<i32>,
+    /// Resource limit the reindeer was stopped for
+    limit: Option<LimitExceeded>,
+    cancelled: Option<CancelReason>,
+    duration_ms: Option<u64>,
+    /// Why the run could not be started or completed
+    error: Option<String>,
+    /// Final result state, as shown in the workbench
+    result: Option<Value>,
+}
+
+impl CliRun {
+    fn passed(&self) -> bool {
+        let Some(result) = &self.result else {
+            return false;
+        };
+        self.error.is_none()
+            && self.stopped().is_none()
+            && self.exit_code == Some(0)
+            && result_error(result).is_none()
+            && failed_tests(result).next().is_none()
+    }
+
+    fn stopped(&self) -> Option<String> {
+        process::stop_message(self.limit, self.cancelled, self.signal)
+    }
+}
+
+/// Write each requested report, with a suite per reindeer that produced
+/// test results or was stopped before it could
+fn write_reports(reports: &[(ReportFormat, PathBuf)], runs: &[CliRun]) -> Result<(), String> {
+    static NO_RESULT: Value = Value::Null;
+    let suites: Vec<TestSuite> = runs
+        .iter()
+        .filter_map(|run| {
+            let stopped = run.stopped();
+            if run.result.is_none() && stopped.is_none() {
+                return None;
+            }
+            Some(TestSuite {
+                reindeer: &run.reindeer,
+                result: run.result.as_ref().unwrap_or(&NO_RESULT),
+                stopped,
+            })
+        })
+        .collect();
+
+    for (format, path) in reports {
+        std::fs::write(path, test_report::render(*format, &suites))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+fn select_reindeer(host: &CliHost, invocation: &Invocation) -> Result<Vec<Reindeer>, String> {
+    let state = host.state().lock().map_err(|e| e.to_string())?;
+    let find = |wanted: &str| {
+        state
+            .reindeer
+            .get(wanted)
+            .or_else(|| state.reindeer.values().find(|r| r.codename == wanted))
+            .cloned()
+            .ok_or_else(|| format!("Reindeer '{}' is not registered", wanted))
+    };
+
+    if !invocation.reindeer.is_empty() {
+        let mut selected: Vec<Reindeer> = Vec::new();
+        for wanted in &invocation.reindeer {
+            let reindeer = find(wanted)?;
+            if !selected.iter().any(|r| r.id == reindeer.id) {
+                selected.push(reindeer);
+            }
+        }
+        return Ok(selected);
+    }
+
+    if invocation.command == CliCommand::Compare {
+        let mut all: Vec<Reindeer> = state.reindeer.values().cloned().collect();
+        all.sort_by(|a, b| (&a.codename, &a.version).cmp(&(&b.codename, &b.version)));
+        if all.len() < 2 {
+            return Err("compare needs at least two registered reindeer".to_string());
+        }
+        return Ok(all);
+    }
+
+    if let Some(default) = &state.settings.default_reindeer {
+        return find(default).map(|reindeer| vec![reindeer]);
+    }
+    match state.reindeer.len() {
+        0 => Err("No reindeer are registered, add one in the workbench".to_string()),
+        1 => Ok(state.reindeer.values().cloned().collect()),
+        _ => Err("Several reindeer are registered, choose one with --reindeer".to_string()),
+    }
+}
+
+fn list_reindeer(host: &CliHost, json: bool) {
+    let Ok(state) = host.state().lock() else {
+        return;
+    };
+    let mut reindeer: Vec<&Reindeer> = state.reindeer.values().collect();
+    reindeer.sort_by(|a, b| (&a.codename, &a.version).cmp(&(&b.codename, &b.version)));
+
+    if json {
+        print_json(&reindeer);
+        return;
+    }
+    for reindeer in reindeer {
+        let default = state.settings.default_reindeer.as_deref() == Some(reindeer.id.as_str());
+        println!(
+            "{}\t{} {}{}\t{}",
+            reindeer.id,
+            reindeer.codename,
+            reindeer.version,
+            if default { " (default)" } else { "" },
+            reindeer.path.display()
+        );
+    }
+}
+
+/// Run the solution from its own directory, as the workbench does, so
+/// relative paths in it resolve the same way
+fn solution_dir(file: &Path) -> Option<String> {
+    let dir = std::path::absolute(file).ok()?.parent()?.to_path_buf();
+    Some(dir.to_string_lossy().to_string())
+}
+
+fn timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
+
+fn execution_id(reindeer: &Reindeer) -> String {
+    format!("{}_{}", reindeer.id, timestamp())
+}
+
+/// A channel printing console output to stderr as it arrives, leaving stdout
+/// for results. Lines are prefixed with the reindeer when there are several.
+fn console_channel(reindeer: &[Reindeer]) -> Channel<ExecutionEvent> {
+    let labels: Vec<(String, String)> = if reindeer.len() > 1 {
+        reindeer
+            .iter()
+            .map(|reindeer| (reindeer.id.clone(), reindeer.codename.clone()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Channel::new(move |body| {
+        let InvokeResponseBody::Json(json) = body else {
+            return Ok(());
+        };
+        let Ok(event) = serde_json::from_str::<Value>(&json) else {
+            return Ok(());
+        };
+
+        // Execution IDs start with the reindeer ID, or end with it in a matrix
+        let label = event
+            .get("execution_id")
+            .and_then(Value::as_str)
+            .and_then(|execution_id| {
+                labels.iter().find(|(id, _)| {
+                    execution_id.starts_with(&format!("{}_", id))
+                        || execution_id.ends_with(&format!("_{}", id))
+                })
+            })
+            .map(|(_, codename)| format!("[{}] ", codename))
+            .unwrap_or_default();
+
+        let data = &event["data"];
+        match event.get("event_type").and_then(Value::as_str) {
+            Some("console") => {
+                for line in data["lines"].as_array().into_iter().flatten() {
+                    eprintln!("{}{}", label, line.as_str().unwrap_or_default());
+                }
+            }
+            Some("stderr") => {
+                eprintln!("{}{}", label, data["message"].as_str().unwrap_or_default())
+            }
+            Some("truncated") => match data["log_path"].as_str() {
+                Some(path) => eprintln!("{}Output truncated, full log written to {}", label, path),
+                None => eprintln!("{}Output truncated", label),
+            },
+            _ => {}
+        }
+        Ok(())
+    })
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("error: {}", e),
+    }
+}
+
+fn report_run(run: &CliRun) {
+    println!("{} {}", run.reindeer.codename, run.reindeer.version);
+
+    if let Some(error) = &run.error {
+        println!("  Error: {}", error);
+        return;
+    }
+    let stopped = run.stopped();
+    let Some(result) = &run.result else {
+        match stopped {
+            Some(stopped) => println!("  No result. {}", stopped),
+            None => println!("  No result (exit code {})", exit_code_label(run.exit_code)),
+        }
+        return;
+    };
+
+    match result.get("type").and_then(Value::as_str) {
+        Some("solution") => {
+            for (part, label) in [("part_one", "Part 1"), ("part_two", "Part 2")] {
+                if let Some(part) = result.get(part).filter(|part| !part["value"].is_null()) {
+                    println!(
+                        "  {}: {}{}",
+                        label,
+                        value_text(&part["value"]),
+                        duration_suffix(part["duration_ms"].as_f64())
+                    );
+                }
+            }
+        }
+        Some("script") => println!(
+            "  Result: {}{}",
+            value_text(&result["value"]),
+            duration_suffix(result["duration_ms"].as_f64())
+        ),
+        Some("test") => report_tests(result),
+        _ => {}
+    }
+
+    if let Some(error) = result_error(result) {
+        println!("  Error: {}", error);
+    }
+    if let Some(stopped) = stopped {
+        println!("  {}", stopped);
+    } else if result_error(result).is_none() && run.exit_code != Some(0) {
+        println!("  Exited with code {}", exit_code_label(run.exit_code));
+    }
+}
+
+fn report_tests(result: &Value) {
+    let tests = result["tests"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for test in tests {
+        let parts: Vec<String> = [("part_one", "Part 1"), ("part_two", "Part 2")]
+            .into_iter()
+            .filter_map(|(part, label)| {
+                let part = test.get(part).filter(|part| !part.is_null())?;
+                Some(if part["passed"].as_bool() == Some(true) {
+                    format!("{} passed", label)
+                } else {
+                    format!(
+                        "{} FAILED (expected {}, got {})",
+                        label,
+                        value_text(&part["expected"]),
+                        value_text(&part["actual"])
+                    )
+                })
+            })
+            .collect();
+        let summary = if parts.is_empty() {
+            "skipped".to_string()
+        } else {
+            parts.join(", ")
+        };
+        println!("  Test {}: {}", test["index"], summary);
+    }
+
+    let failed = failed_tests(result).count();
+    println!("  {} tests, {} failed", tests.len(), failed);
+}
+
+fn report_comparison(result: &MatrixResult, reindeer: &[Reindeer], json: bool) -> bool {
+    let runs_passed = result
+        .runs
+        .iter()
+        .all(|run| run.error.is_none() && run.exit_code == Some(0));
+    let agreed = !result.part_one.disagreement && !result.part_two.disagreement;
+
+    if json {
+        print_json(result);
+        return runs_passed && agreed;
+    }
+
+    let names: HashMap<&str, String> = reindeer
+        .iter()
+        .map(|reindeer| {
+            (
+                reindeer.id.as_str(),
+                format!("{} {}", reindeer.codename, reindeer.version),
+            )
+        })
+        .collect();
+    let name = |impl_id: &str| names.get(impl_id).cloned().unwrap_or(impl_id.to_string());
+
+    for (label, part) in [("Part 1", &result.part_one), ("Part 2", &result.part_two)] {
+        report_consensus(label, part, &name);
+    }
+    for run in &result.runs {
+        if let Some(error) = &run.error {
+            println!("{}: {}", name(&run.impl_id), error);
+        } else if run.exit_code != Some(0) {
+            println!(
+                "{}: exited with code {}",
+                name(&run.impl_id),
+                exit_code_label(run.exit_code)
+            );
+        }
+    }
+
+    runs_passed && agreed
+}
+
+fn report_consensus(label: &str, part: &PartConsensus, name: &impl Fn(&str) -> String) {
+    if part.answers.iter().all(|answer| answer.value.is_none()) {
+        return;
+    }
+    println!(
+        "{}: {}",
+        label,
+        if part.disagreement {
+            "DISAGREE"
+        } else {
+            "agree"
+        }
+    );
+    for answer in &part.answers {
+        println!(
+            "  {}: {}{}",
+            name(&answer.impl_id),
+            answer.value.as_deref().unwrap_or("(no answer)"),
+            duration_suffix(answer.duration_ms)
+        );
+    }
+}
+
+/// The error reported in a result state, with its location if known
+fn result_error(result: &Value) -> Option<String> {
+    let error = result.get("error").filter(|error| !error.is_null())?;
+    let message = error["message"].as_str().unwrap_or("Unknown error");
+    Some(
+        match (
+            error["location"]["line"].as_u64(),
+            error["location"]["column"].as_u64(),
+        ) {
+            (Some(line), Some(column)) => format!("{} (line {}, column {})", message, line, column),
+            _ => message.to_string(),
+        },
+    )
+}
+
+fn failed_tests(result: &Value) -> impl Iterator<Item = &Value> {
+    result["tests"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|test| {
+            ["part_one", "part_two"]
+                .iter()
+                .any(|part| test[part]["passed"].as_bool() == Some(false))
+        })
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => "nothing".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn exit_code_label(exit_code: Option<i32>) -> String {
+    exit_code.map_or("unknown".to_string(), |code| code.to_string())
+}
+
+fn duration_suffix(ms: Option<f64>) -> String {
+    let Some(ms) = ms else {
+        return String::new();
+    };
+    if ms < 1.0 {
+        format!("  ({:.0}µs)", ms * 1000.0)
+    } else if ms < 1000.0 {
+        format!("  ({:.1}ms)", ms)
+    } else {
+        format!("  ({:.2}s)", ms / 1000.0)
+    }
+}
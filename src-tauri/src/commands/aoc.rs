@@ -1,6 +1,8 @@
+use crate::config;
 use crate::state::AppState;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::State;
 
@@ -49,6 +51,86 @@ fn extract_description(html: &str) -> String {
     parts.join("\n<hr/>\n")
 }
 
+/// Find the first aoc:// input reference in santa-lang source,
+/// e.g. read("aoc://2024/15") yields (2024, 15)
+pub(crate) fn extract_aoc_reference(source: &str) -> Option<(u32, u32)> {
+    let re = regex_lite::Regex::new(r#"read\s*\(\s*["']aoc://(\d{4})/(\d{1,2})["']\s*\)"#).ok()?;
+    let caps = re.captures(source)?;
+    let year = caps.get(1)?.as_str().parse().ok()?;
+    let day = caps.get(2)?.as_str().parse().ok()?;
+    Some((year, day))
+}
+
+/// Fetch a puzzle's personal input, or None if AoC rejects the session token
+async fn fetch_input(
+    client: &reqwest::Client,
+    year: u32,
+    day: u32,
+    token: &str,
+) -> Result<Option<String>, String> {
+    let input_url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
+    let input_response = client
+        .get(&input_url)
+        .header("User-Agent", "santa-lang-workbench/1.0")
+        .header("Cookie", format!("session={}", token))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch input: {}", e))?;
+
+    if !input_response.status().is_success() {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        input_response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read input: {}", e))?
+            .trim_end()
+            .to_string(),
+    ))
+}
+
+fn cached_input_path(year: u32, day: u32) -> Result<PathBuf, String> {
+    Ok(config::app_data_dir()?
+        .join("aoc-inputs")
+        .join(format!("{}-{:02}.input", year, day)))
+}
+
+async fn store_cached_input(year: u32, day: u32, input: &str) -> Result<(), String> {
+    let path = cached_input_path(year, day)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tokio::fs::write(&path, input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// A puzzle's input from the local cache, fetching and caching it first if
+/// this is the first time it is needed
+pub(crate) async fn cached_input(
+    year: u32,
+    day: u32,
+    aoc_token: Option<&str>,
+) -> Result<String, String> {
+    let path = cached_input_path(year, day)?;
+    if let Ok(input) = tokio::fs::read_to_string(&path).await {
+        return Ok(input);
+    }
+
+    let token = aoc_token
+        .filter(|token| !token.trim().is_empty())
+        .ok_or("An AoC session token is required to fetch puzzle input")?;
+    let input = fetch_input(&reqwest::Client::new(), year, day, token)
+        .await?
+        .ok_or_else(|| format!("Failed to fetch input for {} day {}", year, day))?;
+    store_cached_input(year, day, &input).await?;
+    Ok(input)
+}
+
 #[tauri::command]
 pub async fn fetch_aoc_puzzle(
     state: State<'_, Mutex<AppState>>,
@@ -88,34 +170,16 @@ pub async fn fetch_aoc_puzzle(
     let description_html = extract_description(&puzzle_html);
 
     // Fetch input if we have a session token
-    let input = if let Some(token) = aoc_token {
-        if !token.trim().is_empty() {
-            let input_url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
-            let input_response = client
-                .get(&input_url)
-                .header("User-Agent", "santa-lang-workbench/1.0")
-                .header("Cookie", format!("session={}", token))
-                .send()
-                .await
-                .map_err(|e| format!("Failed to fetch input: {}", e))?;
-
-            if input_response.status().is_success() {
-                Some(
-                    input_response
-                        .text()
-                        .await
-                        .map_err(|e| format!("Failed to read input: {}", e))?
-                        .trim_end()
-                        .to_string(),
-                )
-            } else {
-                None
+    let input = match aoc_token.filter(|token| !token.trim().is_empty()) {
+        Some(token) => {
+            let input = fetch_input(&client, year, day, &token).await?;
+            if let Some(input) = &input {
+                // Caching is best-effort; the input is still returned
+                let _ = store_cached_input(year, day, input).await;
             }
-        } else {
-            None
+            input
         }
-    } else {
-        None
+        None => None,
     };
 
     Ok(AocPuzzle {
@@ -1,38 +1,228 @@
-use crate::state::AppState;
-use serde::Serialize;
-use std::io::{BufRead, BufReader};
+use crate::benchmark::{BenchmarkSummary, PartDurations};
+use crate::commands::aoc::extract_aoc_reference;
+use crate::commands::formatter::parse_error_location;
+use crate::console_log::{Admission, ConsoleBudget};
+use crate::events::ExecutionEmitter;
+use crate::framing::{self, StdoutFrame};
+use crate::history::{self, HistoryRecord};
+use crate::host::ExecutionHost;
+use crate::input::{self, ExecutionInput, InputDelivery};
+use crate::metrics::MetricsSampler;
+use crate::process::{self, CancelReason, LimitExceeded, ResourceUsage};
+use crate::protocol::{
+    Completion, ConsoleFrame, ConsoleTruncated, ExecutionError, ExecutionEvent, ExecutionPayload,
+    Location, QueuePosition, StderrOutput,
+};
+use crate::recording::Recorder;
+use crate::result_cache::{self, CacheKey, CachedResult};
+use crate::scheduler::ExecutionScheduler;
+use crate::snapshot::{ExecutionSnapshot, ResultState};
+use crate::state::{AppState, ConsoleLimits, ExecutionLimits, LimitOverrides, RunningProcess};
+use crate::temp_file::TempSolutionFile;
+use crate::test_filter::{self, TestIndexMap};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::Mutex;
-use tauri::{Emitter, State, Window};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, State};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::ChildStdout;
+use tokio::time::MissedTickBehavior;
 
-#[derive(Clone, Serialize)]
-pub struct ExecutionEvent {
-    pub execution_id: String,
-    pub event_type: String, // "initial", "patch", "console", "complete", "error"
-    pub data: serde_json::Value,
+/// Optional per-call settings for `run_execution`
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ExecutionOptions {
+    /// Overrides for the limits configured in `Settings`
+    pub limits: Option<LimitOverrides>,
+    /// Number of measured runs in benchmark mode
+    pub iterations: Option<u32>,
+    /// Number of unmeasured runs before measuring in benchmark mode
+    pub warmups: Option<u32>,
+    /// Saved path of the solution, used to group execution history and to
+    /// find the `.input` file saved next to it
+    pub file_path: Option<String>,
+    /// Editor tab the execution belongs to, so watch re-runs can supersede it
+    pub tab_id: Option<String>,
+    /// Puzzle input to run against, replacing any `.input` file next to the
+    /// solution
+    pub input: Option<ExecutionInput>,
+    /// How `input` reaches the reindeer
+    pub input_delivery: InputDelivery,
+    /// In test modes, run only these tests (0-based positions of the `test:`
+    /// blocks in the source)
+    pub test_indices: Option<Vec<usize>>,
+    /// Record every event of the run, with timings, to this file for replay
+    pub record_path: Option<String>,
+    /// Run even if the result cache holds a result for this run
+    pub force: bool,
+}
+
+const DEFAULT_BENCHMARK_ITERATIONS: u32 = 5;
+
+/// Most measured or warmup runs a benchmark may ask for
+const MAX_BENCHMARK_RUNS: u32 = 10_000;
+
+/// How long console lines may wait to be sent as one frame
+const CONSOLE_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Console lines after which a frame is sent without waiting
+const MAX_CONSOLE_FRAME_LINES: usize = 256;
+
+/// Everything needed to spawn a reindeer for one run
+struct ReindeerInvocation {
+    path: PathBuf,
+    args: Vec<String>,
+    working_dir: Option<String>,
+    aoc_token: Option<String>,
+    limits: ExecutionLimits,
+    console_limits: ConsoleLimits,
+    tab_id: Option<String>,
+    /// Written to the reindeer's stdin, which is otherwise left empty
+    stdin: Option<String>,
+    /// Set when tests were filtered by rewriting the source, to report the
+    /// original test indices
+    test_index_map: Option<TestIndexMap>,
+    /// How often to emit live resource samples, if at all
+    metrics_interval: Option<Duration>,
+}
+
+/// How a single reindeer run ended
+struct RunOutcome {
+    exit_code: i32,
+    limit: Option<LimitExceeded>,
+    cancelled: Option<CancelReason>,
+    signal: Option<i32>,
+    /// Final state after applying every JSON Patch to the initial state
+    result: Option<serde_json::Value>,
+    usage: Option<ResourceUsage>,
+}
+
+impl RunOutcome {
+    fn cancelled_before_start(reason: CancelReason) -> Self {
+        RunOutcome {
+            exit_code: -1,
+            limit: None,
+            cancelled: Some(reason),
+            signal: None,
+            result: None,
+            usage: None,
+        }
+    }
+
+    fn succeeded(&self) -> bool {
+        self.exit_code == 0 && self.limit.is_none() && self.cancelled.is_none()
+    }
+}
+
+/// Keeps an execution in `AppState::active_executions` while it is in
+/// progress, so a cancel reaches it before its reindeer has been spawned
+struct ActiveExecution<'a> {
+    state: &'a Mutex<AppState>,
+    execution_id: String,
+}
+
+impl<'a> ActiveExecution<'a> {
+    fn register(state: &'a Mutex<AppState>, execution_id: &str) -> Result<Self, String> {
+        // Watch re-runs register their executions before starting them, so a
+        // cancel recorded in between is kept
+        state
+            .lock()
+            .map_err(|e| e.to_string())?
+            .active_executions
+            .entry(execution_id.to_string())
+            .or_default();
+        Ok(ActiveExecution {
+            state,
+            execution_id: execution_id.to_string(),
+        })
+    }
+}
+
+impl Drop for ActiveExecution<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.active_executions.remove(&self.execution_id);
+        }
+    }
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn run_execution(
-    window: Window,
-    state: State<'_, Mutex<AppState>>,
+    app: AppHandle,
+    execution_id: String,
+    impl_id: String,
+    source: String,
+    mode: String, // "run", "test", "test-slow", "benchmark", "script"
+    working_dir: Option<String>,
+    on_event: Channel<ExecutionEvent>,
+    options: Option<ExecutionOptions>,
+) -> Result<(), String> {
+    execute(
+        &app,
+        execution_id,
+        impl_id,
+        source,
+        mode,
+        working_dir,
+        on_event,
+        options,
+    )
+    .await
+}
+
+/// Run a solution on a reindeer, sending its events on `on_event`. The
+/// result is left in the execution's snapshot.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    host: &impl ExecutionHost,
     execution_id: String,
     impl_id: String,
     source: String,
-    mode: String, // "run", "test", "script"
+    mode: String,
     working_dir: Option<String>,
+    on_event: Channel<ExecutionEvent>,
+    options: Option<ExecutionOptions>,
 ) -> Result<(), String> {
-    let (reindeer_path, aoc_token, debug_mode) = {
-        let state = state.lock().map_err(|e| e.to_string())?;
+    let options = options.unwrap_or_default();
+    let _active = ActiveExecution::register(host.state(), &execution_id)?;
+
+    let (
+        reindeer,
+        aoc_token,
+        debug_mode,
+        limits,
+        console_limits,
+        max_parallel,
+        metrics_interval,
+        use_result_cache,
+    ) = {
+        let state = host.state().lock().map_err(|e| e.to_string())?;
         let reindeer = state.reindeer.get(&impl_id).ok_or("Reindeer not found")?;
         (
-            reindeer.path.clone(),
+            reindeer.clone(),
             state.settings.aoc_session_token.clone(),
             state.settings.debug_mode,
+            state
+                .settings
+                .execution_limits
+                .with_overrides(options.limits),
+            state.settings.console_limits,
+            state.settings.effective_max_parallel(),
+            state
+                .settings
+                .metrics_interval_ms
+                .filter(|&ms| ms > 0)
+                .map(Duration::from_millis),
+            state.settings.result_cache,
         )
     };
 
-    // Build command arguments
+    // Build command arguments (benchmarks time the solution in plain run mode)
     let mut args = vec!["-o".to_string(), "jsonl".to_string()];
 
     match mode.as_str() {
@@ -46,168 +236,792 @@ pub async fn run_execution(
         _ => {}
     }
 
-    // Write source to a temporary file in the working directory if available.
-    // This ensures reindeer implementations (which derive their working directory
-    // from the script's parent) will look for .input files in the correct location.
+    let mut run_source = source.clone();
+    let mut test_index_map = None;
+    if let Some(indices) = &options.test_indices
+        && mode.starts_with("test")
+    {
+        if indices.is_empty() {
+            return Err("No tests selected".to_string());
+        }
+
+        // Explicitly chosen tests run even when marked @slow
+        if mode == "test" {
+            args.push("-s".to_string());
+        }
+
+        if test_filter::reindeer_supports_filter(&reindeer).await {
+            args.push(test_filter::TEST_FILTER_FLAG.to_string());
+            args.push(
+                indices
+                    .iter()
+                    .map(|index| index.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        } else {
+            let (rewritten, index_map) = test_filter::select_tests(&source, indices)?;
+            run_source = rewritten;
+            test_index_map = Some(index_map);
+        }
+    }
+
+    // Without explicit input, the reindeer reads the input saved next to the
+    // solution, copied beside the temporary file it actually runs
+    let input = match (&options.input, &options.file_path) {
+        (Some(input), _) => Some(
+            input
+                .resolve(working_dir.as_deref().map(Path::new), aoc_token.as_deref())
+                .await?,
+        ),
+        (None, Some(path)) => input::read_saved(Path::new(path)).await,
+        (None, None) => None,
+    };
+    let (sibling_input, stdin) = match options.input_delivery {
+        InputDelivery::SiblingFile => (input.as_deref(), None),
+        InputDelivery::Stdin => (None, input.clone()),
+    };
+
     let temp_dir = match &working_dir {
-        Some(dir) => std::path::PathBuf::from(dir),
+        Some(dir) => {
+            let dir = PathBuf::from(dir);
+            remember_working_dir(host, dir.clone())?;
+            dir
+        }
         None => std::env::temp_dir(),
     };
-    let temp_file = temp_dir.join(format!(".santa-workbench-{}.santa", uuid::Uuid::new_v4()));
-    std::fs::write(&temp_file, &source).map_err(|e| e.to_string())?;
 
-    args.push(temp_file.to_string_lossy().to_string());
+    {
+        let mut state = host.state().lock().map_err(|e| e.to_string())?;
+        state.execution_snapshots.insert(
+            execution_id.clone(),
+            ExecutionSnapshot {
+                execution_id: execution_id.clone(),
+                mode: mode.clone(),
+                reindeer: Some(reindeer.clone()),
+                ..Default::default()
+            },
+        );
+    }
+
+    let mut invocation = ReindeerInvocation {
+        path: reindeer.path.clone(),
+        args,
+        working_dir,
+        aoc_token,
+        limits,
+        console_limits,
+        tab_id: options.tab_id.clone(),
+        stdin,
+        test_index_map,
+        metrics_interval,
+    };
+
+    let recorder = match &options.record_path {
+        Some(path) => Some(
+            Recorder::create(Path::new(path))
+                .map_err(|e| format!("Failed to create recording: {}", e))?,
+        ),
+        None => None,
+    };
+    let events = ExecutionEmitter::new(on_event, &execution_id, recorder);
+
+    // Benchmarks are never cached, as their timings are the point of them
+    let cache_key = (use_result_cache && mode != "benchmark").then(|| CacheKey {
+        source: &source,
+        input: input.as_deref(),
+        reindeer: &reindeer,
+        mode: &mode,
+        test_indices: options.test_indices.as_deref(),
+    });
+    if !options.force
+        && let Some(key) = &cache_key
+        && let Some(cached) = result_cache::load(key).await
+    {
+        return replay_cached(&events, host.state(), &execution_id, cached);
+    }
+
+    // Wait for a free slot, reporting the queue position while waiting. The
+    // solution is only written out once it can run, so queued executions
+    // leave no files in the working directory. An execution cancelled while
+    // its input or test filter was resolved does not queue at all.
+    let cancelled = host
+        .state()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .cancel_requested(&execution_id);
+    let permit = match cancelled {
+        Some(_) => None,
+        None => {
+            host.scheduler()
+                .acquire(&execution_id, max_parallel, |position| {
+                    events.emit(ExecutionPayload::Queued(QueuePosition { position }));
+                })
+                .await
+        }
+    };
+
+    let started_at = Instant::now();
+    let outcome = match permit {
+        // Write source to a temporary file in the working directory if available.
+        // This ensures reindeer implementations (which derive their working directory
+        // from the script's parent) will look for .input files in the correct location.
+        // The file is removed when the guard is dropped at the end of the run.
+        Some(_) => match TempSolutionFile::create(&temp_dir, &run_source, sibling_input).await {
+            Ok(temp_file) => {
+                invocation
+                    .args
+                    .push(temp_file.path().to_string_lossy().to_string());
+                if mode == "benchmark" {
+                    run_benchmark(&events, host.state(), &execution_id, &invocation, &options).await
+                } else {
+                    run_reindeer(&events, host.state(), &execution_id, &invocation, true).await
+                }
+            }
+            Err(e) => Err(e.to_string()),
+        },
+        None => {
+            let reason = host
+                .state()
+                .lock()
+                .map_err(|e| e.to_string())?
+                .cancel_requested(&execution_id);
+            Ok(RunOutcome::cancelled_before_start(
+                reason.unwrap_or(CancelReason::User),
+            ))
+        }
+    };
+    let duration_ms = started_at.elapsed().as_millis() as u64;
+    drop(permit);
+
+    {
+        let mut state = host.state().lock().map_err(|e| e.to_string())?;
+        if let Some(snapshot) = state.execution_snapshots.get_mut(&execution_id) {
+            snapshot.complete = true;
+            snapshot.duration_ms = Some(duration_ms);
+            if let Ok(outcome) = &outcome {
+                snapshot.exit_code = Some(outcome.exit_code);
+                snapshot.signal = outcome.signal;
+                snapshot.limit = outcome.limit;
+                snapshot.cancelled = outcome.cancelled;
+            }
+        }
+    }
+    let outcome = outcome?;
+
+    // Build command string for debug mode
+    let command = if debug_mode {
+        Some(format!(
+            "{} {}",
+            invocation.path.display(),
+            invocation.args.join(" ")
+        ))
+    } else {
+        None
+    };
+
+    // Emit completion event
+    events.emit(ExecutionPayload::Complete(Completion {
+        exit_code: outcome.exit_code,
+        signal: outcome.signal,
+        duration_ms,
+        command,
+        limit: outcome.limit,
+        cancelled: outcome.cancelled,
+        usage: outcome.usage,
+        cached: false,
+    }));
+
+    if let Some(key) = &cache_key
+        && outcome.succeeded()
+        && let Some(result) = &outcome.result
+    {
+        let entry = CachedResult {
+            result: result.clone(),
+            exit_code: outcome.exit_code,
+            duration_ms,
+            usage: outcome.usage,
+        };
+        let _ = result_cache::store(key, &entry).await;
+    }
+
+    if outcome.cancelled.is_none() {
+        let (aoc_year, aoc_day) = extract_aoc_reference(&source).unzip();
+        let durations = outcome
+            .result
+            .as_ref()
+            .map(PartDurations::from_state)
+            .unwrap_or_default();
+        let record = HistoryRecord {
+            id: execution_id,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            file_path: options.file_path,
+            aoc_year,
+            aoc_day,
+            source_hash: history::hash_source(&source),
+            reindeer_codename: reindeer.codename,
+            reindeer_version: reindeer.version,
+            mode,
+            result: outcome.result,
+            part_one_ms: durations.part_one,
+            part_two_ms: durations.part_two,
+            exit_code: outcome.exit_code,
+        };
+        let _ = history::append(&record).await;
+    }
+
+    Ok(())
+}
+
+/// Answer an execution from the result cache: the stored final state is sent
+/// as the initial state, followed straight away by completion
+fn replay_cached(
+    events: &ExecutionEmitter,
+    state: &Mutex<AppState>,
+    execution_id: &str,
+    cached: CachedResult,
+) -> Result<(), String> {
+    {
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        if let Some(snapshot) = state.execution_snapshots.get_mut(execution_id) {
+            snapshot.complete = true;
+            snapshot.exit_code = Some(cached.exit_code);
+            snapshot.duration_ms = Some(cached.duration_ms);
+            snapshot.result = Some(cached.result.clone());
+        }
+    }
+
+    events.emit(ExecutionPayload::Initial(cached.result));
+    events.emit(ExecutionPayload::Complete(Completion {
+        exit_code: cached.exit_code,
+        signal: None,
+        duration_ms: cached.duration_ms,
+        command: None,
+        limit: None,
+        cancelled: None,
+        usage: cached.usage,
+        cached: true,
+    }));
+    Ok(())
+}
+
+/// Track the working directory so stale temp files there can be swept on the
+/// next launch, persisting only when a new directory is seen
+fn remember_working_dir(host: &impl ExecutionHost, dir: PathBuf) -> Result<(), String> {
+    let mut state = host.state().lock().map_err(|e| e.to_string())?;
+    if state.remember_working_dir(dir) {
+        host.save_state(&state);
+    }
+    Ok(())
+}
+
+/// Run the solution repeatedly, emitting per-part timing statistics once all
+/// measured runs have finished. Only the final run's results are forwarded so
+/// the output panel still shows the answers.
+async fn run_benchmark(
+    events: &ExecutionEmitter,
+    state: &Mutex<AppState>,
+    execution_id: &str,
+    invocation: &ReindeerInvocation,
+    options: &ExecutionOptions,
+) -> Result<RunOutcome, String> {
+    let iterations = options
+        .iterations
+        .unwrap_or(DEFAULT_BENCHMARK_ITERATIONS)
+        .max(1);
+    let warmups = options.warmups.unwrap_or(0);
+    if iterations > MAX_BENCHMARK_RUNS || warmups > MAX_BENCHMARK_RUNS {
+        return Err(format!(
+            "Benchmarks are limited to {} iterations and {} warmups",
+            MAX_BENCHMARK_RUNS, MAX_BENCHMARK_RUNS
+        ));
+    }
+    let total = warmups.saturating_add(iterations);
+
+    let mut measured = Vec::with_capacity(iterations as usize);
+
+    for run in 0..total {
+        let is_last = run + 1 == total;
+        let outcome = run_reindeer(events, state, execution_id, invocation, is_last).await?;
+
+        if !outcome.succeeded() {
+            return Ok(outcome);
+        }
+        if run >= warmups
+            && let Some(result) = &outcome.result
+        {
+            measured.push(PartDurations::from_state(result));
+        }
+        if is_last {
+            let summary = BenchmarkSummary::from_runs(iterations, warmups, &measured);
+            events.emit(ExecutionPayload::Benchmark(summary));
+            return Ok(outcome);
+        }
+    }
+
+    unreachable!("benchmark always performs at least one run")
+}
+
+/// Spawn the reindeer once and stream its output until it exits. When
+/// `forward_results` is false, stdout is only inspected for timings.
+async fn run_reindeer(
+    events: &ExecutionEmitter,
+    state: &Mutex<AppState>,
+    execution_id: &str,
+    invocation: &ReindeerInvocation,
+    forward_results: bool,
+) -> Result<RunOutcome, String> {
+    let limits = invocation.limits;
+
+    // A cancel may have arrived at any point since the execution started,
+    // including between benchmark runs
+    let cancelled = state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .cancel_requested(execution_id);
+    if let Some(reason) = cancelled {
+        return Ok(RunOutcome::cancelled_before_start(reason));
+    }
 
     // Spawn the process
-    let mut cmd = Command::new(&reindeer_path);
-    cmd.args(&args)
-        .stdin(Stdio::null())
+    let mut cmd = Command::new(&invocation.path);
+    cmd.args(&invocation.args)
+        .stdin(if invocation.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
     // Set working directory if provided
-    if let Some(dir) = working_dir {
+    if let Some(dir) = &invocation.working_dir {
         cmd.current_dir(dir);
     }
 
     // Set AoC session token if available
-    if let Some(token) = aoc_token {
+    if let Some(token) = &invocation.aoc_token {
         cmd.env("SANTA_CLI_SESSION_TOKEN", token);
     }
 
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| format!("Failed to spawn process: {}", e))?;
+    process::isolate_process_group(&mut cmd);
+    process::apply_resource_limits(&mut cmd, &limits);
+
+    let process::SpawnedReindeer {
+        pid,
+        stdin,
+        stdout,
+        stderr,
+        mut exit,
+    } = process::spawn_reindeer(&mut cmd).map_err(|e| format!("Failed to spawn process: {}", e))?;
 
     // Store process ID for potential cancellation (keyed by execution_id)
-    let pid = child.id();
-    {
+    let cancel_reason = {
         let mut state = state.lock().map_err(|e| e.to_string())?;
-        state.running_processes.insert(execution_id.clone(), pid);
+        let cancel_reason = state.cancel_requested(execution_id);
+        state.track_process(
+            execution_id.to_string(),
+            RunningProcess {
+                pid,
+                executable: invocation.path.clone(),
+                tab_id: invocation.tab_id.clone(),
+                cancel_reason,
+            },
+        );
+        cancel_reason
+    };
+    // Cancelled while it was being spawned, so cancel_execution could not see
+    // its PID
+    if cancel_reason.is_some() {
+        tokio::spawn(process::terminate_process_group(
+            pid,
+            process::TERMINATE_GRACE_PERIOD,
+        ));
     }
 
+    // Sample live resource use of forwarded runs only, so benchmark timings
+    // are not disturbed by the sampler
+    let sampler = match invocation.metrics_interval {
+        Some(interval) if forward_results => {
+            Some(MetricsSampler::start(events.clone(), pid, interval))
+        }
+        _ => None,
+    };
+
+    // Feed input on its own task, closing stdin once written so the reindeer
+    // sees end-of-file. A reindeer that exits without reading it all is fine.
+    if let (Some(input), Some(mut stdin)) = (invocation.stdin.clone(), stdin) {
+        tokio::spawn(async move {
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+    }
+
+    // Console output from both streams counts against the same limits
+    let console = Arc::new(Mutex::new(ConsoleBudget::new(
+        execution_id,
+        invocation.console_limits,
+    )));
+
+    // Drain stderr on its own task so a chatty reindeer cannot fill the pipe
+    // and block while we are still reading stdout. Like stdout, it is only
+    // forwarded for forwarded runs.
+    let stderr_reader = {
+        let events = events.clone();
+        let console = console.clone();
+        tokio::spawn(async move {
+            let mut reported_out_of_memory = false;
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let line = line.trim_end();
+                if line.is_empty() {
+                    continue;
+                }
+                reported_out_of_memory |= process::is_out_of_memory_message(line);
+                if !forward_results {
+                    continue;
+                }
+                match admit_console_line(&console, line) {
+                    Admission::Forward => events.emit(stderr_output(line)),
+                    Admission::Truncated(log_path) => events.emit(console_truncated(log_path)),
+                    Admission::Spilled => {}
+                }
+            }
+            reported_out_of_memory
+        })
+    };
+
     // Read stdout line by line
-    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
-    let reader = BufReader::new(stdout);
+    let mut results = ResultState::default();
+    let mut pending_console = Vec::new();
+    let streaming = async {
+        stream_stdout(
+            events,
+            state,
+            stdout,
+            forward_results,
+            invocation.test_index_map.as_ref(),
+            &console,
+            &mut pending_console,
+            &mut results,
+        )
+        .await;
+        (&mut exit).await
+    };
 
-    let mut is_first_line = true;
+    // Wait for process to complete, stopping it if the wall-clock limit is hit
+    let (exited, timed_out) = match limits.timeout_secs {
+        Some(secs) => match tokio::time::timeout(Duration::from_secs(secs), streaming).await {
+            Ok(exited) => (exited, false),
+            Err(_) => {
+                process::terminate_process_group(pid, process::TERMINATE_GRACE_PERIOD).await;
+                (exit.await, true)
+            }
+        },
+        None => (streaming.await, false),
+    };
+    // Lines still waiting for a frame when the timeout cut streaming short
+    flush_console(events, &mut pending_console);
+    if let Some(sampler) = sampler {
+        sampler.stop().await;
+    }
+    let process::ProcessExit { status, usage } = exited
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    let reported_out_of_memory = stderr_reader.await.unwrap_or(false);
+
+    // Remove from running processes, noting whether it was cancelled
+    let cancelled = {
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        state
+            .untrack_process(execution_id)
+            .and_then(|process| process.cancel_reason)
+    };
+
+    let limit = if cancelled.is_some() {
+        None
+    } else if timed_out {
+        Some(LimitExceeded::Timeout)
+    } else {
+        process::detect_limit_exceeded(&status, &usage, &limits, reported_out_of_memory)
+    };
+
+    #[cfg(unix)]
+    let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+    #[cfg(not(unix))]
+    let signal = None;
+
+    Ok(RunOutcome {
+        exit_code: status.code().unwrap_or(-1),
+        signal,
+        limit,
+        cancelled,
+        result: results.into_state(),
+        usage: Some(usage),
+    })
+}
 
-    for line in reader.lines() {
-        match line {
-            Ok(line) => {
+/// Forward each stdout line as a protocol or console event until the stream
+/// closes, materializing the result state along the way. Console lines are
+/// batched into frames in `console`, flushed before any other event to keep
+/// the order. Forwarded runs also publish the materialized state as the
+/// execution's snapshot.
+#[allow(clippy::too_many_arguments)]
+async fn stream_stdout(
+    events: &ExecutionEmitter,
+    state: &Mutex<AppState>,
+    stdout: ChildStdout,
+    forward: bool,
+    test_index_map: Option<&TestIndexMap>,
+    console_budget: &Mutex<ConsoleBudget>,
+    console: &mut Vec<String>,
+    results: &mut ResultState,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+    let mut frame_timer = tokio::time::interval_at(
+        tokio::time::Instant::now() + CONSOLE_FRAME_INTERVAL,
+        CONSOLE_FRAME_INTERVAL,
+    );
+    frame_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        let next = tokio::select! {
+            next = lines.next_line() => next,
+            _ = frame_timer.tick(), if !console.is_empty() => {
+                flush_console(events, console);
+                continue;
+            }
+        };
+
+        match next {
+            Ok(None) => break,
+            Ok(Some(line)) => {
                 let line = line.trim();
                 if line.is_empty() {
                     continue;
                 }
 
-                // Try to parse as JSON
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
-                    let event = if is_first_line {
-                        is_first_line = false;
-                        ExecutionEvent {
-                            execution_id: execution_id.clone(),
-                            event_type: "initial".to_string(),
-                            data: json,
+                let frame = framing::classify(line, results.is_initialized());
+                let applied = match frame {
+                    StdoutFrame::Initial(mut json) => {
+                        if let Some(index_map) = test_index_map {
+                            index_map.remap_initial(&mut json);
                         }
-                    } else {
-                        // Subsequent lines are JSON Patch arrays
-                        ExecutionEvent {
-                            execution_id: execution_id.clone(),
-                            event_type: "patch".to_string(),
-                            data: json,
+                        results
+                            .set_initial(json.clone())
+                            .map(|()| ExecutionPayload::Initial(json))
+                    }
+                    StdoutFrame::Patch(mut json) => {
+                        if let Some(index_map) = test_index_map {
+                            index_map.remap_patch(&mut json);
                         }
-                    };
+                        results
+                            .apply_patch(&json)
+                            .map(|()| ExecutionPayload::Patch(json))
+                    }
+                    StdoutFrame::Console => {
+                        if forward {
+                            // A frame waits a full interval from its first line
+                            if console.is_empty() {
+                                frame_timer.reset();
+                            }
+                            forward_console_line(events, console_budget, console, line);
+                        }
+                        continue;
+                    }
+                };
 
-                    let _ = window.emit("execution-event", &event);
-                } else {
-                    // Non-JSON lines are console output (from puts())
-                    let _ = window.emit(
-                        "execution-event",
-                        ExecutionEvent {
-                            execution_id: execution_id.clone(),
-                            event_type: "console".to_string(),
-                            data: serde_json::json!({ "message": line }),
-                        },
-                    );
+                if !forward {
+                    continue;
                 }
+
+                let payload = applied.unwrap_or_else(|message| {
+                    ExecutionPayload::Error(ExecutionError::new(message))
+                });
+                flush_console(events, console);
+                events.emit(payload);
+                publish_snapshot(state, events.execution_id(), results);
             }
             Err(e) => {
-                let _ = window.emit(
-                    "execution-event",
-                    ExecutionEvent {
-                        execution_id: execution_id.clone(),
-                        event_type: "error".to_string(),
-                        data: serde_json::json!({ "message": e.to_string() }),
-                    },
-                );
+                flush_console(events, console);
+                events.emit(ExecutionPayload::Error(ExecutionError::new(e.to_string())));
+                break;
             }
         }
     }
 
-    // Wait for process to complete
-    let status = child.wait().map_err(|e| e.to_string())?;
-    let exit_code = status.code().unwrap_or(-1);
+    flush_console(events, console);
+}
 
-    // Remove from running processes
-    {
-        let mut state = state.lock().map_err(|e| e.to_string())?;
-        state.running_processes.remove(&execution_id);
+/// Queue a console line for the next frame, or spill it once over the limits
+fn forward_console_line(
+    events: &ExecutionEmitter,
+    console_budget: &Mutex<ConsoleBudget>,
+    console: &mut Vec<String>,
+    line: &str,
+) {
+    match admit_console_line(console_budget, line) {
+        Admission::Forward => {
+            console.push(line.to_string());
+            if console.len() >= MAX_CONSOLE_FRAME_LINES {
+                flush_console(events, console);
+            }
+        }
+        Admission::Truncated(log_path) => {
+            flush_console(events, console);
+            events.emit(console_truncated(log_path));
+        }
+        Admission::Spilled => {}
     }
+}
 
-    // Clean up temp file
-    let _ = std::fs::remove_file(&temp_file);
+fn admit_console_line(budget: &Mutex<ConsoleBudget>, line: &str) -> Admission {
+    match budget.lock() {
+        Ok(mut budget) => budget.admit(line),
+        Err(_) => Admission::Forward,
+    }
+}
 
-    // Build command string for debug mode
-    let command = if debug_mode {
-        Some(format!("{} {}", reindeer_path.display(), args.join(" ")))
-    } else {
-        None
+fn console_truncated(log_path: Option<PathBuf>) -> ExecutionPayload {
+    ExecutionPayload::Truncated(ConsoleTruncated {
+        log_path: log_path.map(|path| path.to_string_lossy().to_string()),
+    })
+}
+
+/// Send any pending console lines as a single frame
+fn flush_console(events: &ExecutionEmitter, console: &mut Vec<String>) {
+    if !console.is_empty() {
+        events.emit(ExecutionPayload::Console(ConsoleFrame {
+            lines: std::mem::take(console),
+        }));
+    }
+}
+
+/// Copy the latest materialized state into the execution's snapshot
+fn publish_snapshot(state: &Mutex<AppState>, execution_id: &str, results: &ResultState) {
+    if let Ok(mut state) = state.lock()
+        && let Some(snapshot) = state.execution_snapshots.get_mut(execution_id)
+    {
+        snapshot.result = results.state().cloned();
+        snapshot.patches_applied = results.patches_applied();
+    }
+}
+
+/// Build the payload for a stderr line, including the source location when
+/// the reindeer reports one (e.g. "Error at line 5, column 12: ...")
+fn stderr_output(line: &str) -> ExecutionPayload {
+    let (line_number, column, message) = parse_error_location(line);
+    let location = match (line_number, column) {
+        (Some(line), Some(column)) => Some(Location { line, column }),
+        _ => None,
     };
+    ExecutionPayload::Stderr(StderrOutput { message, location })
+}
 
-    // Emit completion event
-    let _ = window.emit(
-        "execution-event",
-        ExecutionEvent {
-            execution_id: execution_id.clone(),
-            event_type: "complete".to_string(),
-            data: serde_json::json!({
-                "exit_code": exit_code,
-                "command": command
-            }),
-        },
-    );
+/// The backend's materialized result for an execution, for windows or tools
+/// that did not observe its event stream from the start
+#[tauri::command]
+pub fn get_execution_snapshot(
+    state: State<'_, Mutex<AppState>>,
+    execution_id: String,
+) -> Result<Option<ExecutionSnapshot>, String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    Ok(state.execution_snapshots.get(&execution_id).cloned())
+}
 
+#[tauri::command]
+pub fn discard_execution_snapshot(
+    state: State<'_, Mutex<AppState>>,
+    execution_id: String,
+) -> Result<(), String> {
+    let mut state = state.lock().map_err(|e| e.to_string())?;
+    state.execution_snapshots.remove(&execution_id);
     Ok(())
 }
 
 #[tauri::command]
-pub fn cancel_execution(
+pub async fn cancel_execution(
     state: State<'_, Mutex<AppState>>,
+    scheduler: State<'_, ExecutionScheduler>,
     execution_id: String,
+    reason: Option<CancelReason>,
 ) -> Result<(), String> {
+    let reason = reason.unwrap_or(CancelReason::User);
     let pid = {
-        let state = state.lock().map_err(|e| e.to_string())?;
-        state.running_processes.get(&execution_id).copied()
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        // Recorded for every stage of the execution, so one that has not
+        // spawned its reindeer yet never does
+        if let Some(requested) = state.active_executions.get_mut(&execution_id) {
+            requested.get_or_insert(reason);
+        }
+        state
+            .running_processes
+            .get_mut(&execution_id)
+            .map(|process| {
+                process.cancel_reason = Some(reason);
+                process.pid
+            })
     };
 
+    // Executions still waiting for a slot are simply withdrawn from the queue
+    if scheduler.cancel(&execution_id) {
+        return Ok(());
+    }
+
+    // The entry is removed by run_execution once the reindeer has exited, so
+    // the completion event can report why it stopped
     if let Some(pid) = pid {
-        #[cfg(unix)]
-        {
-            use std::process::Command;
-            let _ = Command::new("kill").args(["-9", &pid.to_string()]).output();
-        }
+        process::terminate_process_group(pid, process::TERMINATE_GRACE_PERIOD).await;
+    }
 
-        #[cfg(windows)]
-        {
-            use std::process::Command;
-            let _ = Command::new("taskkill")
-                .args(["/F", "/PID", &pid.to_string()])
-                .output();
-        }
+    Ok(())
+}
 
-        let mut state = state.lock().map_err(|e| e.to_string())?;
-        state.running_processes.remove(&execution_id);
+/// Stop every tracked reindeer, including REPL sessions, blocking until each
+/// has exited or been killed.
+/// Called when the app exits.
+pub fn terminate_all_executions(state: &Mutex<AppState>) {
+    let pids = mark_all_for_exit(state);
+    if !pids.is_empty() {
+        tauri::async_runtime::block_on(terminate_process_groups(pids));
     }
+}
 
-    Ok(())
+/// Stop every tracked reindeer without waiting for them to exit.
+/// Called when the main window closes, so the event loop is not blocked.
+pub fn spawn_terminate_all_executions(state: &Mutex<AppState>) {
+    let pids = mark_all_for_exit(state);
+    if !pids.is_empty() {
+        tauri::async_runtime::spawn(terminate_process_groups(pids));
+    }
+}
+
+/// Note that every tracked reindeer is being stopped by the app exiting,
+/// returning their PIDs
+fn mark_all_for_exit(state: &Mutex<AppState>) -> Vec<u32> {
+    match state.lock() {
+        Ok(mut state) => {
+            for requested in state.active_executions.values_mut() {
+                requested.get_or_insert(CancelReason::AppExit);
+            }
+            for process in state.running_processes.values_mut() {
+                process.cancel_reason.get_or_insert(CancelReason::AppExit);
+            }
+            state.tracked_pids().collect()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+async fn terminate_process_groups(pids: Vec<u32>) {
+    let mut terminations = tokio::task::JoinSet::new();
+    for pid in pids {
+        terminations.spawn(process::terminate_process_group(
+            pid,
+            process::TERMINATE_GRACE_PERIOD,
+        ));
+    }
+    while terminations.join_next().await.is_some() {}
 }
@@ -1,9 +1,10 @@
-use crate::config::FORMATTER_REPO;
+use crate::config::{self, FORMATTER_REPO};
 use crate::state::AppState;
+use regex_lite::Regex;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Command;
-use std::sync::Mutex;
+use std::sync::{LazyLock, Mutex};
 use tauri::{AppHandle, State};
 
 /// Parse version string into (major, minor, patch) tuple
@@ -194,10 +195,7 @@ pub async fn download_formatter(
     asset_name: String,
 ) -> Result<String, String> {
     // Determine download directory
-    let download_dir = dirs::data_local_dir()
-        .ok_or("Could not find local data directory")?
-        .join("santa-lang-workbench")
-        .join("formatter");
+    let download_dir = config::app_data_dir()?.join("formatter");
 
     std::fs::create_dir_all(&download_dir).map_err(|e| e.to_string())?;
 
@@ -304,7 +302,15 @@ pub fn format_code(
     }
 }
 
-fn parse_error_location(stderr: &str) -> (Option<u32>, Option<u32>, String) {
+/// "Error at line 5, column 12: unexpected token"
+static LINE_COLUMN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"line\s+(\d+),?\s*column\s+(\d+)").expect("pattern is valid"));
+
+/// "5:12: unexpected token"
+static LINE_COLUMN_PREFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+):(\d+):").expect("pattern is valid"));
+
+pub(crate) fn parse_error_location(stderr: &str) -> (Option<u32>, Option<u32>, String) {
     // Try to extract line/column from error message
     // Common formats:
     // - "Error at line 5, column 12: unexpected token"
@@ -314,20 +320,14 @@ fn parse_error_location(stderr: &str) -> (Option<u32>, Option<u32>, String) {
     let stderr_trimmed = stderr.trim();
 
     // Try "line X, column Y" pattern
-    if let Some(caps) = regex_lite::Regex::new(r"line\s+(\d+),?\s*column\s+(\d+)")
-        .ok()
-        .and_then(|re| re.captures(stderr_trimmed))
-    {
+    if let Some(caps) = LINE_COLUMN.captures(stderr_trimmed) {
         let line = caps.get(1).and_then(|m| m.as_str().parse().ok());
         let column = caps.get(2).and_then(|m| m.as_str().parse().ok());
         return (line, column, stderr_trimmed.to_string());
     }
 
     // Try "X:Y:" pattern (common in many compilers)
-    if let Some(caps) = regex_lite::Regex::new(r"^(\d+):(\d+):")
-        .ok()
-        .and_then(|re| re.captures(stderr_trimmed))
-    {
+    if let Some(caps) = LINE_COLUMN_PREFIX.captures(stderr_trimmed) {
         let line = caps.get(1).and_then(|m| m.as_str().parse().ok());
         let column = caps.get(2).and_then(|m| m.as_str().parse().ok());
         return (line, column, stderr_trimmed.to_string());
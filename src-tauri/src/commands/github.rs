@@ -1,4 +1,4 @@
-use crate::config::get_repo_for_codename;
+use crate::config::{self, get_repo_for_codename};
 use serde::{Deserialize, Serialize};
 
 /// Check if a version tag represents >= 1.0.1
@@ -77,11 +77,7 @@ pub async fn download_reindeer(
     asset_name: String,
 ) -> Result<String, String> {
     // Determine download directory
-    let download_dir = dirs::data_local_dir()
-        .ok_or("Could not find local data directory")?
-        .join("santa-lang-workbench")
-        .join("reindeer")
-        .join(&codename);
+    let download_dir = config::app_data_dir()?.join("reindeer").join(&codename);
 
     std::fs::create_dir_all(&download_dir).map_err(|e| e.to_string())?;
 
@@ -0,0 +1,20 @@
+use crate::history::{self, HistoryFilter, HistoryRecord};
+use crate::result_cache;
+
+/// Query past executions by file, AoC year/day and/or reindeer codename
+#[tauri::command]
+pub async fn get_execution_history(
+    filter: Option<HistoryFilter>,
+) -> Result<Vec<HistoryRecord>, String> {
+    history::query(&filter.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn clear_execution_history() -> Result<(), String> {
+    history::clear().await
+}
+
+#[tauri::command]
+pub async fn clear_result_cache() -> Result<(), String> {
+    result_cache::clear().await
+}
@@ -0,0 +1,125 @@
+use crate::commands::execution::{ExecutionOptions, execute};
+use crate::host::ExecutionHost;
+use crate::matrix::{MatrixResult, MatrixRun};
+use crate::protocol::ExecutionEvent;
+use tauri::AppHandle;
+use tauri::ipc::Channel;
+
+/// Run the solution on every listed reindeer and compare their answers. Each
+/// run is an ordinary execution with id `{matrix_id}_{impl_id}`, so it shares
+/// the scheduler's concurrency limit, can be cancelled on its own and streams
+/// its events on `on_event` as it goes. Resolves with the aggregated result
+/// once every run has finished.
+#[tauri::command]
+pub async fn run_matrix(
+    app: AppHandle,
+    matrix_id: String,
+    impl_ids: Vec<String>,
+    source: String,
+    working_dir: Option<String>,
+    on_event: Channel<ExecutionEvent>,
+    options: Option<ExecutionOptions>,
+) -> Result<MatrixResult, String> {
+    compare(
+        &app,
+        matrix_id,
+        impl_ids,
+        source,
+        working_dir,
+        on_event,
+        options,
+    )
+    .await
+}
+
+/// Run a matrix, see `run_matrix`
+pub async fn compare(
+    host: &impl ExecutionHost,
+    matrix_id: String,
+    impl_ids: Vec<String>,
+    source: String,
+    working_dir: Option<String>,
+    on_event: Channel<ExecutionEvent>,
+    options: Option<ExecutionOptions>,
+) -> Result<MatrixResult, String> {
+    let mut unique_ids: Vec<String> = Vec::with_capacity(impl_ids.len());
+    for impl_id in impl_ids {
+        if !unique_ids.contains(&impl_id) {
+            unique_ids.push(impl_id);
+        }
+    }
+    if unique_ids.is_empty() {
+        return Err("No reindeer selected".to_string());
+    }
+
+    // Runs share the solution and input but not a recording file
+    let options = ExecutionOptions {
+        record_path: None,
+        ..options.unwrap_or_default()
+    };
+
+    let launched: Vec<_> = unique_ids
+        .into_iter()
+        .map(|impl_id| {
+            let execution_id = format!("{}_{}", matrix_id, impl_id);
+            let task = {
+                let host = host.clone();
+                let execution_id = execution_id.clone();
+                let impl_id = impl_id.clone();
+                let source = source.clone();
+                let working_dir = working_dir.clone();
+                let on_event = on_event.clone();
+                let options = options.clone();
+                tauri::async_runtime::spawn(async move {
+                    execute(
+                        &host,
+                        execution_id,
+                        impl_id,
+                        source,
+                        "run".to_string(),
+                        working_dir,
+                        on_event,
+                        Some(options),
+                    )
+                    .await
+                })
+            };
+            (impl_id, execution_id, task)
+        })
+        .collect();
+
+    let mut runs = Vec::with_capacity(launched.len());
+    for (impl_id, execution_id, task) in launched {
+        let error = match task.await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e),
+            Err(e) => Some(e.to_string()),
+        };
+
+        // The matrix result carries each run's answers, so its snapshot is
+        // no longer needed
+        let snapshot = host
+            .state()
+            .lock()
+            .map_err(|e| e.to_string())?
+            .execution_snapshots
+            .remove(&execution_id);
+
+        let (exit_code, duration_ms, result) = match snapshot {
+            Some(snapshot) => (snapshot.exit_code, snapshot.duration_ms, snapshot.result),
+            None => (None, None, None),
+        };
+        runs.push((
+            MatrixRun {
+                impl_id,
+                execution_id,
+                exit_code,
+                duration_ms,
+                error,
+            },
+            result,
+        ));
+    }
+
+    Ok(MatrixResult::from_runs(matrix_id, runs))
+}
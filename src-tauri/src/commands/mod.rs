@@ -2,5 +2,11 @@ pub mod aoc;
 pub mod execution;
 pub mod formatter;
 pub mod github;
+pub mod history;
+pub mod matrix;
+pub mod recording;
 pub mod reindeer;
+pub mod repl;
+pub mod report;
 pub mod settings;
+pub mod watch;
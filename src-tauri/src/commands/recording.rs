@@ -0,0 +1,46 @@
+use crate::recording::{self, RecordedEvent};
+use std::path::Path;
+use std::time::Duration;
+use tauri::ipc::Channel;
+
+/// Re-send the events of a recorded run as `execution_id`, keeping the
+/// original gaps between them divided by `speed` (1 by default, 0 to emit
+/// everything at once). Resolves once the last event has been emitted.
+#[tauri::command]
+pub async fn replay_recording(
+    path: String,
+    execution_id: String,
+    speed: Option<f64>,
+    on_event: Channel<serde_json::Value>,
+) -> Result<(), String> {
+    let speed = speed.unwrap_or(1.0);
+    if speed.is_nan() || speed < 0.0 {
+        return Err("Replay speed must not be negative".to_string());
+    }
+
+    let recording = recording::read(Path::new(&path)).await?;
+    if recording.is_empty() {
+        return Err("Recording contains no events".to_string());
+    }
+
+    let started_at = tokio::time::Instant::now();
+    for RecordedEvent {
+        elapsed_ms,
+        mut event,
+    } in recording
+    {
+        if speed > 0.0 {
+            let due = Duration::try_from_secs_f64(elapsed_ms as f64 / 1000.0 / speed)
+                .ok()
+                .and_then(|offset| started_at.checked_add(offset))
+                .ok_or("Replay speed is too slow")?;
+            tokio::time::sleep_until(due).await;
+        }
+        if let Some(event) = event.as_object_mut() {
+            event.insert("execution_id".to_string(), execution_id.clone().into());
+        }
+        let _ = on_event.send(event);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use crate::process;
+use crate::protocol::{REPL_EVENT, ReplClosed, ReplEvent, ReplOutput, ReplPayload};
+use crate::state::{AppState, ExecutionLimits, ReplSession};
+use std::process::Stdio;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager, State, Window};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+
+/// Start a reindeer's REPL, returning the session ID used to send it input.
+/// Output is streamed back as `repl-event`s until the session closes.
+#[tauri::command]
+pub async fn start_repl_session(
+    window: Window,
+    state: State<'_, Mutex<AppState>>,
+    impl_id: String,
+    working_dir: Option<String>,
+) -> Result<String, String> {
+    let (reindeer, aoc_token, max_memory_mb) = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        let reindeer = state.reindeer.get(&impl_id).ok_or("Reindeer not found")?;
+        (
+            reindeer.clone(),
+            state.settings.aoc_session_token.clone(),
+            state.settings.execution_limits.max_memory_mb,
+        )
+    };
+
+    let mut cmd = Command::new(&reindeer.path);
+    cmd.arg("-r")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    if let Some(dir) = &working_dir {
+        cmd.current_dir(dir);
+    }
+    if let Some(token) = &aoc_token {
+        cmd.env("SANTA_CLI_SESSION_TOKEN", token);
+    }
+
+    // A session is open-ended, so only the memory limit applies
+    process::isolate_process_group(cmd.as_std_mut());
+    process::apply_resource_limits(
+        cmd.as_std_mut(),
+        &ExecutionLimits {
+            max_memory_mb,
+            ..Default::default()
+        },
+    );
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn REPL: {}", e))?;
+    let pid = child.id().ok_or("REPL exited immediately")?;
+    let mut stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let (input, mut lines) = tokio::sync::mpsc::unbounded_channel::<String>();
+
+    {
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        state.track_repl_session(
+            session_id.clone(),
+            ReplSession {
+                pid,
+                executable: reindeer.path.clone(),
+                input,
+            },
+        );
+    }
+
+    // Write input in order on a single task; stdin closes once the session
+    // is untracked and the sender dropped
+    tokio::spawn(async move {
+        while let Some(line) = lines.recv().await {
+            let written = async {
+                stdin.write_all(line.as_bytes()).await?;
+                stdin.write_all(b"\n").await?;
+                stdin.flush().await
+            };
+            if written.await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let stdout_forwarder = tokio::spawn(forward_output(
+        window.clone(),
+        session_id.clone(),
+        stdout,
+        ReplPayload::Stdout,
+    ));
+    let stderr_forwarder = tokio::spawn(forward_output(
+        window.clone(),
+        session_id.clone(),
+        stderr,
+        ReplPayload::Stderr,
+    ));
+
+    {
+        let session_id = session_id.clone();
+        tokio::spawn(async move {
+            let exit_code = match child.wait().await {
+                Ok(status) => status.code().unwrap_or(-1),
+                Err(_) => -1,
+            };
+            let _ = stdout_forwarder.await;
+            let _ = stderr_forwarder.await;
+
+            if let Ok(mut state) = window.state::<Mutex<AppState>>().lock() {
+                state.untrack_repl_session(&session_id);
+            }
+            let _ = window.emit(
+                REPL_EVENT,
+                ReplEvent::new(&session_id, ReplPayload::Closed(ReplClosed { exit_code })),
+            );
+        });
+    }
+
+    Ok(session_id)
+}
+
+/// Send an expression to a REPL session. Its evaluation arrives as output
+/// events.
+#[tauri::command]
+pub fn send_repl_input(
+    state: State<'_, Mutex<AppState>>,
+    session_id: String,
+    input: String,
+) -> Result<(), String> {
+    let state = state.lock().map_err(|e| e.to_string())?;
+    let session = state
+        .repl_sessions
+        .get(&session_id)
+        .ok_or("REPL session not found")?;
+    session
+        .input
+        .send(input)
+        .map_err(|_| "REPL session has closed".to_string())
+}
+
+/// Stop a REPL session. A `closed` event follows once the reindeer has exited.
+#[tauri::command]
+pub async fn close_repl_session(
+    state: State<'_, Mutex<AppState>>,
+    session_id: String,
+) -> Result<(), String> {
+    let pid = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        state
+            .repl_sessions
+            .get(&session_id)
+            .map(|session| session.pid)
+    };
+
+    if let Some(pid) = pid {
+        process::terminate_process_group(pid, process::TERMINATE_GRACE_PERIOD).await;
+    }
+
+    Ok(())
+}
+
+/// Emit output from one of the REPL's streams as it is read
+async fn forward_output(
+    window: Window,
+    session_id: String,
+    mut reader: impl AsyncRead + Unpin,
+    payload: fn(ReplOutput) -> ReplPayload,
+) {
+    let mut buffer = [0u8; 4096];
+    let mut pending = Vec::new();
+
+    loop {
+        let read = match reader.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        pending.extend_from_slice(&buffer[..read]);
+
+        // Hold back a multi-byte character split across reads
+        let complete = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => pending.len(),
+        };
+        if complete == 0 {
+            continue;
+        }
+
+        let text = String::from_utf8_lossy(&pending[..complete]).into_owned();
+        pending.drain(..complete);
+        let _ = window.emit(
+            REPL_EVENT,
+            ReplEvent::new(&session_id, payload(ReplOutput { text })),
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use crate::process;
+use crate::state::AppState;
+use crate::test_report::{self, ReportFormat, TestSuite};
+use std::sync::Mutex;
+use tauri::State;
+
+/// Write the results of a finished test execution to `path` as a JUnit XML
+/// or TAP report
+#[tauri::command]
+pub async fn export_test_report(
+    state: State<'_, Mutex<AppState>>,
+    execution_id: String,
+    format: ReportFormat,
+    path: String,
+) -> Result<(), String> {
+    let report = {
+        let state = state.lock().map_err(|e| e.to_string())?;
+        let snapshot = state
+            .execution_snapshots
+            .get(&execution_id)
+            .ok_or("Execution not found")?;
+        if !snapshot.mode.starts_with("test") {
+            return Err("Only test executions can be exported".to_string());
+        }
+        if !snapshot.complete {
+            return Err("Execution has not finished".to_string());
+        }
+        let result = snapshot
+            .result
+            .as_ref()
+            .ok_or("Execution produced no test results")?;
+        let reindeer = snapshot.reindeer.as_ref().ok_or("Reindeer not found")?;
+        let stopped = process::stop_message(snapshot.limit, snapshot.cancelled, snapshot.signal);
+        test_report::render(
+            format,
+            &[TestSuite {
+                reindeer,
+                result,
+                stopped,
+            }],
+        )
+    };
+
+    tokio::fs::write(&path, report)
+        .await
+        .map_err(|e| format!("Failed to write report: {}", e))
+}
@@ -0,0 +1,172 @@
+use crate::commands::execution::{ExecutionOptions, cancel_execution, execute};
+use crate::input::{self, ExecutionInput};
+use crate::process::CancelReason;
+use crate::protocol::ExecutionEvent;
+use crate::scheduler::ExecutionScheduler;
+use crate::state::AppState;
+use crate::watch::{self, WatchRegistry};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::ipc::Channel;
+use tauri::{Emitter, Manager, State, Window};
+
+/// Emitted before a watch-triggered execution starts, so the frontend can
+/// create its execution entry ahead of the execution events
+#[derive(Clone, Serialize, JsonSchema)]
+pub struct WatchRun {
+    pub tab_id: String,
+    pub execution_id: String,
+    pub impl_id: String,
+    pub mode: String,
+}
+
+/// Re-run `path` with the given reindeer, mode and options whenever it, its
+/// sibling `.input` file or an explicit input file changes on disk, sending
+/// every re-run's events on `on_event`. Replaces any existing watch for the
+/// tab.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn start_watch(
+    window: Window,
+    watches: State<'_, WatchRegistry>,
+    tab_id: String,
+    path: String,
+    impl_ids: Vec<String>,
+    mode: String,
+    on_event: Channel<ExecutionEvent>,
+    options: Option<ExecutionOptions>,
+) -> Result<(), String> {
+    let solution = PathBuf::from(&path);
+    if !solution.is_file() {
+        return Err("File does not exist".to_string());
+    }
+    let options = ExecutionOptions {
+        file_path: Some(path),
+        tab_id: Some(tab_id.clone()),
+        ..options.unwrap_or_default()
+    };
+
+    let mut watched = vec![solution.clone(), input::sibling_input_path(&solution)];
+    if let Some(ExecutionInput::File { path }) = &options.input {
+        // Relative input paths resolve against the solution's directory,
+        // which re-runs use as their working directory
+        watched.push(match solution.parent() {
+            Some(dir) => dir.join(path),
+            None => PathBuf::from(path),
+        });
+    }
+
+    let task = {
+        let tab_id = tab_id.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                watch::wait_for_change(&watched).await;
+                rerun(
+                    &window, &on_event, &tab_id, &solution, &impl_ids, &mode, &options,
+                )
+                .await;
+            }
+        })
+    };
+
+    watches.insert(tab_id, task);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_watch(watches: State<'_, WatchRegistry>, tab_id: String) -> Result<bool, String> {
+    Ok(watches.remove(&tab_id))
+}
+
+/// Cancel whatever is still running for the tab, then start a fresh
+/// execution per reindeer against the solution as it is now on disk
+#[allow(clippy::too_many_arguments)]
+async fn rerun(
+    window: &Window,
+    on_event: &Channel<ExecutionEvent>,
+    tab_id: &str,
+    solution: &Path,
+    impl_ids: &[String],
+    mode: &str,
+    options: &ExecutionOptions,
+) {
+    let Ok(source) = tokio::fs::read_to_string(solution).await else {
+        return;
+    };
+    let working_dir = solution
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string());
+
+    let watches = window.state::<WatchRegistry>();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let execution_ids: Vec<String> = impl_ids
+        .iter()
+        .map(|impl_id| format!("{}_{}", impl_id, timestamp))
+        .collect();
+
+    let mut in_flight = watches.replace_launched(tab_id, execution_ids.clone());
+    if let Ok(mut state) = window.state::<Mutex<AppState>>().lock() {
+        // Register the new executions before they start, so a later change
+        // can supersede them at any stage
+        for execution_id in &execution_ids {
+            state
+                .active_executions
+                .entry(execution_id.clone())
+                .or_default();
+        }
+        in_flight.extend(
+            state
+                .running_processes
+                .iter()
+                .filter(|(_, process)| process.tab_id.as_deref() == Some(tab_id))
+                .map(|(execution_id, _)| execution_id.clone()),
+        );
+    }
+    for execution_id in in_flight {
+        let _ = cancel_execution(
+            window.state::<Mutex<AppState>>(),
+            window.state::<ExecutionScheduler>(),
+            execution_id,
+            Some(CancelReason::Superseded),
+        )
+        .await;
+    }
+
+    for (impl_id, execution_id) in impl_ids.iter().zip(execution_ids) {
+        let _ = window.emit(
+            "watch-run",
+            WatchRun {
+                tab_id: tab_id.to_string(),
+                execution_id: execution_id.clone(),
+                impl_id: impl_id.clone(),
+                mode: mode.to_string(),
+            },
+        );
+
+        let window = window.clone();
+        let impl_id = impl_id.clone();
+        let source = source.clone();
+        let mode = mode.to_string();
+        let working_dir = working_dir.clone();
+        let on_event = on_event.clone();
+        let options = options.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = execute(
+                window.app_handle(),
+                execution_id,
+                impl_id,
+                source,
+                mode,
+                working_dir,
+                on_event,
+                Some(options),
+            )
+            .await;
+        });
+    }
+}
@@ -1,3 +1,16 @@
+use std::path::PathBuf;
+
+/// Matches `identifier` in tauri.conf.json, which names the config directory
+pub const APP_IDENTIFIER: &str = "com.eddmann.santa-lang-workbench";
+
+/// Where history, caches, logs and downloads are kept, shared by the app and
+/// the CLI
+pub fn app_data_dir() -> Result<PathBuf, String> {
+    Ok(dirs::data_local_dir()
+        .ok_or("Could not find local data directory")?
+        .join(APP_IDENTIFIER))
+}
+
 pub const FORMATTER_REPO: &str = "eddmann/santa-lang-tinsel";
 
 pub const IMPLEMENTATIONS: &[(&str, &str, &str)] = &[
@@ -0,0 +1,113 @@
+use crate::config;
+use crate::state::ConsoleLimits;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Logs older than this are removed at startup
+const LOG_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// What to do with a console line
+pub enum Admission {
+    /// Send it to the frontend
+    Forward,
+    /// The first line over the limits. It and everything after it goes to the
+    /// log at this path, if the log could be created.
+    Truncated(Option<PathBuf>),
+    /// Over the limits, already written to the log
+    Spilled,
+}
+
+/// Caps how much console output of a run reaches the frontend. Once a cap is
+/// hit, the full output so far and everything after it is written to a log
+/// file in the app data dir instead.
+pub struct ConsoleBudget {
+    execution_id: String,
+    limits: ConsoleLimits,
+    lines: usize,
+    bytes: usize,
+    /// Lines forwarded so far, written at the top of the log once it is
+    /// created so the log holds the complete output
+    forwarded: Vec<String>,
+    log: Option<BufWriter<File>>,
+    truncated: bool,
+}
+
+impl ConsoleBudget {
+    pub fn new(execution_id: impl Into<String>, limits: ConsoleLimits) -> Self {
+        ConsoleBudget {
+            execution_id: execution_id.into(),
+            limits,
+            lines: 0,
+            bytes: 0,
+            forwarded: Vec::new(),
+            log: None,
+            truncated: false,
+        }
+    }
+
+    pub fn admit(&mut self, line: &str) -> Admission {
+        if self.truncated {
+            self.write_to_log(line);
+            return Admission::Spilled;
+        }
+
+        let lines = self.lines + 1;
+        let bytes = self.bytes + line.len() + 1;
+        let within_limits = self.limits.max_lines.is_none_or(|max| lines <= max)
+            && self.limits.max_bytes.is_none_or(|max| bytes <= max);
+        if within_limits {
+            self.lines = lines;
+            self.bytes = bytes;
+            if self.limits.is_limited() {
+                self.forwarded.push(line.to_string());
+            }
+            return Admission::Forward;
+        }
+
+        self.truncated = true;
+        let path = self.open_log();
+        for forwarded in std::mem::take(&mut self.forwarded) {
+            self.write_to_log(&forwarded);
+        }
+        self.write_to_log(line);
+        Admission::Truncated(path)
+    }
+
+    fn open_log(&mut self) -> Option<PathBuf> {
+        let path = logs_dir()?.join(format!("{}.log", self.execution_id));
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        self.log = Some(BufWriter::new(File::create(&path).ok()?));
+        Some(path)
+    }
+
+    fn write_to_log(&mut self, line: &str) {
+        if let Some(log) = &mut self.log {
+            let _ = writeln!(log, "{}", line);
+        }
+    }
+}
+
+fn logs_dir() -> Option<PathBuf> {
+    Some(config::app_data_dir().ok()?.join("console-logs"))
+}
+
+/// Remove console logs older than the retention period
+pub fn sweep_stale() {
+    let Some(entries) = logs_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let is_stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > LOG_RETENTION);
+        if is_stale {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::protocol::{ExecutionEvent, ExecutionPayload};
+use crate::recording::Recorder;
+use std::sync::Arc;
+use tauri::ipc::Channel;
+
+/// Delivers one execution's events over the channel the frontend passed in,
+/// mirroring them into a recording when one was requested
+#[derive(Clone)]
+pub struct ExecutionEmitter {
+    channel: Channel<ExecutionEvent>,
+    execution_id: String,
+    recorder: Option<Arc<Recorder>>,
+}
+
+impl ExecutionEmitter {
+    pub fn new(
+        channel: Channel<ExecutionEvent>,
+        execution_id: impl Into<String>,
+        recorder: Option<Recorder>,
+    ) -> Self {
+        ExecutionEmitter {
+            channel,
+            execution_id: execution_id.into(),
+            recorder: recorder.map(Arc::new),
+        }
+    }
+
+    pub fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
+
+    pub fn emit(&self, payload: ExecutionPayload) {
+        let event = ExecutionEvent::new(&self.execution_id, payload);
+        if let Some(recorder) = &self.recorder {
+            recorder.record(&event);
+        }
+        let _ = self.channel.send(event);
+    }
+}
@@ -0,0 +1,221 @@
+use serde_json::Value;
+
+/// Result state types a reindeer reports in its initial line
+const RESULT_TYPES: &[&str] = &["solution", "test", "script"];
+
+/// What a line of reindeer stdout carries
+pub enum StdoutFrame {
+    /// The initial result state
+    Initial(Value),
+    /// A JSON Patch against the result state
+    Patch(Value),
+    /// Anything else, including JSON a solution printed with `puts`
+    Console,
+}
+
+/// Classify a stdout line. Before the initial state has been seen only an
+/// initial state object is accepted as protocol; afterwards only a JSON Patch
+/// operation array is. Everything else is console output.
+pub fn classify(line: &str, initialized: bool) -> StdoutFrame {
+    // Protocol lines are always a single object or array, so cheaply skip
+    // anything that cannot be one before parsing
+    let expected_start = if initialized { '[' } else { '{' };
+    if !line.starts_with(expected_start) {
+        return StdoutFrame::Console;
+    }
+
+    match serde_json::from_str::<Value>(line) {
+        Ok(json) if !initialized && is_initial_state(&json) => StdoutFrame::Initial(json),
+        Ok(json) if initialized && is_patch(&json) => StdoutFrame::Patch(json),
+        _ => StdoutFrame::Console,
+    }
+}
+
+/// An object with a known result `type` and a `status`, or for scripts the
+/// `success` flag the reindeer reports instead
+fn is_initial_state(json: &Value) -> bool {
+    let Some(state) = json.as_object() else {
+        return false;
+    };
+    let Some(kind) = state
+        .get("type")
+        .and_then(Value::as_str)
+        .filter(|kind| RESULT_TYPES.contains(kind))
+    else {
+        return false;
+    };
+    state.get("status").is_some_and(Value::is_string)
+        || (kind == "script" && state.get("success").is_some_and(Value::is_boolean))
+}
+
+/// A non-empty array of well-formed RFC 6902 operations
+fn is_patch(json: &Value) -> bool {
+    json.as_array()
+        .is_some_and(|operations| !operations.is_empty() && operations.iter().all(is_operation))
+}
+
+fn is_operation(operation: &Value) -> bool {
+    let Some(operation) = operation.as_object() else {
+        return false;
+    };
+    let is_pointer = |field: &str| {
+        operation
+            .get(field)
+            .and_then(Value::as_str)
+            .is_some_and(|pointer| pointer.is_empty() || pointer.starts_with('/'))
+    };
+    if !is_pointer("path") {
+        return false;
+    }
+
+    match operation.get("op").and_then(Value::as_str) {
+        Some("add" | "replace" | "test") => operation.contains_key("value"),
+        Some("remove") => true,
+        Some("move" | "copy") => is_pointer("from"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::ResultState;
+    use serde_json::json;
+
+    const SOLUTION_TRANSCRIPT: &str = r#"{"type":"solution","status":"pending","part_one":{"status":"pending","value":null,"duration_ms":null},"part_two":{"status":"pending","value":null,"duration_ms":null},"error":null}
+[{"op":"replace","path":"/status","value":"running"},{"op":"replace","path":"/part_one/status","value":"running"}]
+[1,2,3]
+{"elf":"Buddy","calories":24000}
+[{"op":"replace","path":"/part_one/status","value":"complete"},{"op":"replace","path":"/part_one/value","value":"24000"},{"op":"replace","path":"/part_one/duration_ms","value":5}]
+[{"op":"replace","path":"/part_two/status","value":"running"}]
+[{"op":"replace","path":"/part_two/status","value":"complete"},{"op":"replace","path":"/part_two/value","value":"45000"},{"op":"replace","path":"/part_two/duration_ms","value":7},{"op":"replace","path":"/status","value":"complete"}]
+"#;
+
+    const TEST_TRANSCRIPT: &str = r#"{"type":"test","status":"pending","tests":[{"index":1,"slow":false,"status":"pending","part_one":null,"part_two":null},{"index":2,"slow":true,"status":"pending","part_one":null,"part_two":null}],"error":null}
+[{"op":"replace","path":"/status","value":"running"},{"op":"replace","path":"/tests/0/status","value":"running"}]
+[1,2,3]
+{"type":"debug"}
+[{"op":"replace","path":"/tests/0","value":{"index":1,"slow":false,"status":"complete","part_one":{"passed":true,"expected":"24000","actual":"24000"},"part_two":{"passed":false,"expected":"45000","actual":"41000"}}}]
+[{"op":"replace","path":"/tests/1/status","value":"complete"},{"op":"replace","path":"/status","value":"complete"}]
+"#;
+
+    const SCRIPT_TRANSCRIPT: &str = r#"[1,2,3]
+{"type":"solution"}
+{"type":"script","success":true,"value":"6","duration_ms":3}
+"#;
+
+    /// Feed a transcript through `classify` as `stream_stdout` does,
+    /// returning the materialized state and the lines treated as console
+    fn materialize(transcript: &str) -> (Option<Value>, Vec<&str>) {
+        let mut results = ResultState::default();
+        let mut console = Vec::new();
+        for line in transcript.lines() {
+            match classify(line, results.is_initialized()) {
+                StdoutFrame::Initial(json) => results.set_initial(json).unwrap(),
+                StdoutFrame::Patch(json) => results.apply_patch(&json).unwrap(),
+                StdoutFrame::Console => console.push(line),
+            }
+        }
+        (results.into_state(), console)
+    }
+
+    #[test]
+    fn materializes_solution_transcript() {
+        let (state, console) = materialize(SOLUTION_TRANSCRIPT);
+
+        assert_eq!(
+            state,
+            Some(json!({
+                "type": "solution",
+                "status": "complete",
+                "part_one": { "status": "complete", "value": "24000", "duration_ms": 5 },
+                "part_two": { "status": "complete", "value": "45000", "duration_ms": 7 },
+                "error": null
+            }))
+        );
+        assert_eq!(console, ["[1,2,3]", r#"{"elf":"Buddy","calories":24000}"#]);
+    }
+
+    #[test]
+    fn materializes_test_transcript() {
+        let (state, console) = materialize(TEST_TRANSCRIPT);
+
+        assert_eq!(
+            state,
+            Some(json!({
+                "type": "test",
+                "status": "complete",
+                "tests": [
+                    {
+                        "index": 1,
+                        "slow": false,
+                        "status": "complete",
+                        "part_one": { "passed": true, "expected": "24000", "actual": "24000" },
+                        "part_two": { "passed": false, "expected": "45000", "actual": "41000" }
+                    },
+                    {
+                        "index": 2,
+                        "slow": true,
+                        "status": "complete",
+                        "part_one": null,
+                        "part_two": null
+                    }
+                ],
+                "error": null
+            }))
+        );
+        assert_eq!(console, ["[1,2,3]", r#"{"type":"debug"}"#]);
+    }
+
+    #[test]
+    fn materializes_script_transcript_reporting_success() {
+        let (state, console) = materialize(SCRIPT_TRANSCRIPT);
+
+        assert_eq!(
+            state,
+            Some(json!({ "type": "script", "success": true, "value": "6", "duration_ms": 3 }))
+        );
+        assert_eq!(console, ["[1,2,3]", r#"{"type":"solution"}"#]);
+    }
+
+    #[test]
+    fn accepts_failed_script_as_initial_state() {
+        let line = r#"{"type":"script","success":false,"error":{"message":"Unexpected token","location":{"line":1,"column":4}}}"#;
+
+        assert!(matches!(classify(line, false), StdoutFrame::Initial(_)));
+    }
+
+    #[test]
+    fn treats_unknown_objects_as_console_before_initialization() {
+        for line in [
+            r#"{"type":"unknown","status":"pending"}"#,
+            r#"{"type":"solution","success":true}"#,
+            r#"{"status":"pending"}"#,
+            "{not json",
+        ] {
+            assert!(
+                matches!(classify(line, false), StdoutFrame::Console),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn treats_malformed_patches_as_console_after_initialization() {
+        for line in [
+            "[]",
+            "[1,2,3]",
+            r#"[{"op":"replace","path":"/status"}]"#,
+            r#"[{"op":"explode","path":"/status","value":1}]"#,
+            r#"[{"op":"add","path":"status","value":1}]"#,
+            r#"{"type":"solution","status":"pending"}"#,
+        ] {
+            assert!(
+                matches!(classify(line, true), StdoutFrame::Console),
+                "{}",
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::config;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Size past which the history file is compacted, keeping the newest records
+/// that fit in half of it
+const MAX_HISTORY_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Serializes appends with compaction, which rewrites the file
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// A completed execution, stored as one line of the history file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryRecord {
+    pub id: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub file_path: Option<String>,
+    pub aoc_year: Option<u32>,
+    pub aoc_day: Option<u32>,
+    pub source_hash: String,
+    pub reindeer_codename: String,
+    pub reindeer_version: String,
+    pub mode: String,
+    pub result: Option<serde_json::Value>,
+    pub part_one_ms: Option<f64>,
+    pub part_two_ms: Option<f64>,
+    pub exit_code: i32,
+}
+
+/// Criteria for querying history; unset fields match every record
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct HistoryFilter {
+    pub file_path: Option<String>,
+    pub aoc_year: Option<u32>,
+    pub aoc_day: Option<u32>,
+    pub reindeer_codename: Option<String>,
+    /// Only return the most recent N records
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, record: &HistoryRecord) -> bool {
+        self.file_path
+            .as_ref()
+            .is_none_or(|path| record.file_path.as_ref() == Some(path))
+            && self
+                .aoc_year
+                .is_none_or(|year| record.aoc_year == Some(year))
+            && self.aoc_day.is_none_or(|day| record.aoc_day == Some(day))
+            && self
+                .reindeer_codename
+                .as_ref()
+                .is_none_or(|codename| *codename == record.reindeer_codename)
+    }
+}
+
+pub fn hash_source(source: &str) -> String {
+    format!("{:x}", Sha256::digest(source.as_bytes()))
+}
+
+fn history_path() -> Result<PathBuf, String> {
+    Ok(config::app_data_dir()?.join("history.jsonl"))
+}
+
+pub async fn append(record: &HistoryRecord) -> Result<(), String> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    line.push('\n');
+
+    let _guard = WRITE_LOCK.lock().await;
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+    file.write_all(line.as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let size = file.metadata().await.map_err(|e| e.to_string())?.len();
+    if size > MAX_HISTORY_BYTES {
+        compact(&path).await?;
+    }
+    Ok(())
+}
+
+/// Drop the oldest records, keeping the newest that fit in half of
+/// `MAX_HISTORY_BYTES`
+async fn compact(path: &Path) -> Result<(), String> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut kept = 0;
+    let mut retained: Vec<&str> = content
+        .lines()
+        .rev()
+        .take_while(|line| {
+            kept += line.len() as u64 + 1;
+            kept <= MAX_HISTORY_BYTES / 2
+        })
+        .collect();
+    retained.reverse();
+
+    let mut compacted = retained.join("\n");
+    compacted.push('\n');
+
+    // Replace the file atomically so a crash cannot lose the whole history
+    let temp_path = path.with_extension("jsonl.tmp");
+    tokio::fs::write(&temp_path, compacted)
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::fs::rename(&temp_path, path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Return matching records, newest first
+pub async fn query(filter: &HistoryFilter) -> Result<Vec<HistoryRecord>, String> {
+    let path = history_path()?;
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    // Skip lines that fail to parse (e.g. a partial write from a crash)
+    let records = content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<HistoryRecord>(line).ok())
+        .filter(|record| filter.matches(record));
+
+    Ok(match filter.limit {
+        Some(limit) => records.take(limit).collect(),
+        None => records.collect(),
+    })
+}
+
+pub async fn clear() -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().await;
+    match tokio::fs::remove_file(history_path()?).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
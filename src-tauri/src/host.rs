@@ -0,0 +1,29 @@
+use crate::scheduler::ExecutionScheduler;
+use crate::state::AppState;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// What the execution pipeline needs from whatever is running it, so the app
+/// and the headless CLI share the same run semantics
+pub trait ExecutionHost: Clone + Send + Sync + 'static {
+    fn state(&self) -> &Mutex<AppState>;
+
+    fn scheduler(&self) -> &ExecutionScheduler;
+
+    /// Persist the app state after the pipeline changed it
+    fn save_state(&self, state: &AppState);
+}
+
+impl ExecutionHost for AppHandle {
+    fn state(&self) -> &Mutex<AppState> {
+        Manager::state::<Mutex<AppState>>(self).inner()
+    }
+
+    fn scheduler(&self) -> &ExecutionScheduler {
+        Manager::state::<ExecutionScheduler>(self).inner()
+    }
+
+    fn save_state(&self, state: &AppState) {
+        let _ = state.save(self);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::commands::aoc;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Puzzle input supplied explicitly for an execution, instead of relying on
+/// the reindeer finding a `.input` file next to the solution
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExecutionInput {
+    /// Input typed or pasted into the workbench
+    Text { content: String },
+    /// A file on disk, relative paths resolving against the working directory
+    File { path: String },
+    /// The Advent of Code input for a puzzle, fetched once and then cached
+    Aoc { year: u32, day: u32 },
+}
+
+/// The `.input` file a reindeer reads for a solution, e.g. `day01.input` for
+/// `day01.santa`. Input saved next to a solution is copied to this path
+/// beside the temporary solution, so the reindeer sees the same file.
+pub fn sibling_input_path(solution: &Path) -> PathBuf {
+    solution.with_extension("input")
+}
+
+/// Read the input saved next to a solution, if there is one
+pub async fn read_saved(solution: &Path) -> Option<String> {
+    tokio::fs::read_to_string(sibling_input_path(solution))
+        .await
+        .ok()
+}
+
+/// How resolved input reaches the reindeer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InputDelivery {
+    /// Written to the `.input` file alongside the temporary solution
+    #[default]
+    SiblingFile,
+    /// Piped to the reindeer's stdin
+    Stdin,
+}
+
+impl ExecutionInput {
+    /// Read the input's content
+    pub async fn resolve(
+        &self,
+        working_dir: Option<&Path>,
+        aoc_token: Option<&str>,
+    ) -> Result<String, String> {
+        match self {
+            ExecutionInput::Text { content } => Ok(content.clone()),
+            ExecutionInput::File { path } => {
+                let path = match working_dir {
+                    Some(dir) => dir.join(path),
+                    None => PathBuf::from(path),
+                };
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|e| format!("Failed to read input {}: {}", path.display(), e))
+            }
+            ExecutionInput::Aoc { year, day } => aoc::cached_input(*year, *day, aoc_token).await,
+        }
+    }
+}
@@ -1,9 +1,35 @@
+mod benchmark;
+pub mod bindings;
+pub mod cli;
 mod commands;
 mod config;
+mod console_log;
+mod events;
+mod framing;
+mod history;
+mod host;
+mod input;
+mod matrix;
 mod menu;
+mod metrics;
+mod orphans;
+mod process;
+mod protocol;
+mod recording;
+mod result_cache;
+mod scheduler;
+mod snapshot;
 mod state;
+mod temp_file;
+mod test_filter;
+mod test_report;
+mod watch;
 
-use tauri::{Emitter, Manager};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager, RunEvent, WindowEvent};
+
+/// Label of the window that owns executions, as configured in tauri.conf.json
+const MAIN_WINDOW_LABEL: &str = "main";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -14,7 +40,22 @@ pub fn run() {
         .plugin(tauri_plugin_http::init())
         .setup(|app| {
             let state = state::AppState::load(app.handle())?;
-            app.manage(std::sync::Mutex::new(state));
+
+            // Kill any reindeer left running by a previous crash
+            orphans::reap_survivors();
+
+            // Remove temp solution files orphaned by a previous crash. This
+            // finishes before the commands are managed, so it never races a run.
+            let mut sweep_dirs = state.recent_working_dirs.clone();
+            sweep_dirs.push(std::env::temp_dir());
+            temp_file::sweep_stale(sweep_dirs);
+
+            // Remove console logs past their retention period
+            std::thread::spawn(console_log::sweep_stale);
+
+            app.manage(Mutex::new(state));
+            app.manage(scheduler::ExecutionScheduler::default());
+            app.manage(watch::WatchRegistry::default());
 
             // Create and set native menu
             let menu = menu::create_menu(app.handle())?;
@@ -27,6 +68,15 @@ pub fn run() {
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let WindowEvent::Destroyed = event
+                && window.label() == MAIN_WINDOW_LABEL
+            {
+                window.state::<watch::WatchRegistry>().clear();
+                let state = window.state::<Mutex<state::AppState>>();
+                commands::execution::spawn_terminate_all_executions(&state);
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             commands::reindeer::get_reindeer,
             commands::reindeer::add_reindeer,
@@ -36,6 +86,19 @@ pub fn run() {
             commands::github::download_reindeer,
             commands::execution::run_execution,
             commands::execution::cancel_execution,
+            commands::execution::get_execution_snapshot,
+            commands::execution::discard_execution_snapshot,
+            commands::matrix::run_matrix,
+            commands::recording::replay_recording,
+            commands::report::export_test_report,
+            commands::repl::start_repl_session,
+            commands::repl::send_repl_input,
+            commands::repl::close_repl_session,
+            commands::watch::start_watch,
+            commands::watch::stop_watch,
+            commands::history::get_execution_history,
+            commands::history::clear_execution_history,
+            commands::history::clear_result_cache,
             commands::settings::get_settings,
             commands::settings::save_settings,
             commands::formatter::get_formatter_status,
@@ -45,6 +108,13 @@ pub fn run() {
             commands::formatter::check_formatter_update,
             commands::aoc::fetch_aoc_puzzle,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let RunEvent::Exit = event {
+                app_handle.state::<watch::WatchRegistry>().clear();
+                let state = app_handle.state::<Mutex<state::AppState>>();
+                commands::execution::terminate_all_executions(&state);
+            }
+        });
 }
@@ -0,0 +1,103 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+/// How one reindeer's run of a matrix ended
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MatrixRun {
+    pub impl_id: String,
+    pub execution_id: String,
+    /// None if the reindeer could not be started
+    pub exit_code: Option<i32>,
+    /// Wall-clock time of the run, excluding time spent queued
+    pub duration_ms: Option<u64>,
+    /// Why the run could not be started or completed
+    pub error: Option<String>,
+}
+
+/// One reindeer's answer to a part
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MatrixAnswer {
+    pub impl_id: String,
+    /// None if the reindeer did not produce an answer
+    pub value: Option<String>,
+    /// Time the reindeer reported for solving the part
+    pub duration_ms: Option<f64>,
+}
+
+/// Every reindeer's answer to a part
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PartConsensus {
+    /// In the order the reindeer were requested
+    pub answers: Vec<MatrixAnswer>,
+    /// Set when the answers differ, including when only some reindeer
+    /// produced one
+    pub disagreement: bool,
+}
+
+/// The same solution run on several reindeer, with their answers compared
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MatrixResult {
+    pub matrix_id: String,
+    pub runs: Vec<MatrixRun>,
+    pub part_one: PartConsensus,
+    pub part_two: PartConsensus,
+}
+
+impl MatrixResult {
+    /// Compare the final solution state of each run
+    pub fn from_runs(matrix_id: String, runs: Vec<(MatrixRun, Option<Value>)>) -> Self {
+        let part = |name: &str| {
+            PartConsensus::from_answers(
+                runs.iter()
+                    .map(|(run, result)| MatrixAnswer::from_state(&run.impl_id, result, name))
+                    .collect(),
+            )
+        };
+        let part_one = part("part_one");
+        let part_two = part("part_two");
+
+        MatrixResult {
+            matrix_id,
+            runs: runs.into_iter().map(|(run, _)| run).collect(),
+            part_one,
+            part_two,
+        }
+    }
+}
+
+impl MatrixAnswer {
+    fn from_state(impl_id: &str, state: &Option<Value>, part: &str) -> Self {
+        let part = state.as_ref().and_then(|state| state.get(part));
+        let value = part
+            .and_then(|part| part.get("value"))
+            .and_then(|value| match value {
+                Value::Null => None,
+                Value::String(value) => Some(value.clone()),
+                value => Some(value.to_string()),
+            });
+
+        MatrixAnswer {
+            impl_id: impl_id.to_string(),
+            value,
+            duration_ms: part
+                .and_then(|part| part.get("duration_ms"))
+                .and_then(Value::as_f64),
+        }
+    }
+}
+
+impl PartConsensus {
+    fn from_answers(answers: Vec<MatrixAnswer>) -> Self {
+        // A part no reindeer answered, such as a missing part two, is not
+        // a disagreement
+        let disagreement = answers
+            .windows(2)
+            .any(|pair| pair[0].value != pair[1].value);
+
+        PartConsensus {
+            answers,
+            disagreement,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::events::ExecutionEmitter;
+use crate::protocol::{ExecutionPayload, ResourceSample};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// A sampler emitting `metrics` events for a running reindeer, stopped once
+/// the reindeer has exited
+pub struct MetricsSampler {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl MetricsSampler {
+    /// Sample the process group led by `pid` every `interval`
+    pub fn start(events: ExecutionEmitter, pid: u32, interval: Duration) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let started_at = Instant::now();
+            let mut previous = ProcessGroupUsage::sample(pid).await;
+            let mut sampled_at = Instant::now();
+
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+
+                let Some(current) = ProcessGroupUsage::sample(pid).await else {
+                    break;
+                };
+                let cpu_ms = previous
+                    .map(|previous| current.cpu_ms.saturating_sub(previous.cpu_ms))
+                    .unwrap_or(current.cpu_ms);
+                let elapsed_ms = sampled_at.elapsed().as_millis().max(1) as u64;
+                sampled_at = Instant::now();
+                previous = Some(current);
+
+                events.emit(ExecutionPayload::Metrics(ResourceSample {
+                    elapsed_ms: started_at.elapsed().as_millis() as u64,
+                    rss_bytes: current.rss_bytes,
+                    cpu_percent: cpu_ms as f64 * 100.0 / elapsed_ms as f64,
+                    processes: current.processes,
+                }));
+            }
+        });
+
+        MetricsSampler { stop, task }
+    }
+
+    /// Stop sampling, waiting for any in-flight sample to be emitted
+    pub async fn stop(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// Resource usage summed over every process in a process group
+#[derive(Debug, Clone, Copy)]
+struct ProcessGroupUsage {
+    rss_bytes: u64,
+    /// User plus system CPU time consumed so far
+    cpu_ms: u64,
+    processes: u32,
+}
+
+impl ProcessGroupUsage {
+    /// Read the group's usage on the blocking pool, as scanning /proc reads a
+    /// file per process on the system
+    async fn sample(pgid: u32) -> Option<Self> {
+        tokio::task::spawn_blocking(move || Self::read(pgid))
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Read the group's usage from /proc, or None once it has no members
+    #[cfg(target_os = "linux")]
+    fn read(pgid: u32) -> Option<Self> {
+        // SAFETY: sysconf has no preconditions
+        let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+
+        let mut usage = ProcessGroupUsage {
+            rss_bytes: 0,
+            cpu_ms: 0,
+            processes: 0,
+        };
+
+        for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+            let is_pid = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()));
+            if !is_pid {
+                continue;
+            }
+            let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+                continue;
+            };
+            let Some(process) = ProcStat::parse(&stat) else {
+                continue;
+            };
+            if process.pgrp != pgid {
+                continue;
+            }
+
+            usage.processes += 1;
+            usage.cpu_ms += (process.utime + process.stime) * 1000 / ticks_per_sec;
+            usage.rss_bytes += process.rss_pages * page_size;
+        }
+
+        (usage.processes > 0).then_some(usage)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read(_pgid: u32) -> Option<Self> {
+        None
+    }
+}
+
+/// The fields of /proc/<pid>/stat used for sampling
+#[cfg(target_os = "linux")]
+struct ProcStat {
+    pgrp: u32,
+    utime: u64,
+    stime: u64,
+    rss_pages: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl ProcStat {
+    fn parse(stat: &str) -> Option<Self> {
+        // The command name is parenthesized and may itself contain spaces or
+        // parentheses, so fields are counted from after its closing paren
+        let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+        // Fields are numbered from 1 in proc(5); the first here is field 3
+        let field = |number: usize| fields.get(number - 3)?.parse::<u64>().ok();
+
+        Some(ProcStat {
+            pgrp: field(5)? as u32,
+            utime: field(14)?,
+            stime: field(15)?,
+            rss_pages: field(24)?,
+        })
+    }
+}
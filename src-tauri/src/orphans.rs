@@ -0,0 +1,143 @@
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// A spawned reindeer as recorded on disk, so it can be found again if the
+/// workbench crashes while it is running
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackedProcess {
+    pid: u32,
+    executable: PathBuf,
+}
+
+fn tracked_processes_path() -> Option<PathBuf> {
+    Some(config::app_data_dir().ok()?.join("running-processes.json"))
+}
+
+/// Sends each new set of running reindeer to the thread that writes them out
+static WRITER: OnceLock<Sender<Vec<TrackedProcess>>> = OnceLock::new();
+
+/// Record the currently running reindeer by PID and executable, replacing
+/// any previous record. The file is written on a background thread, so this
+/// is cheap enough to call while holding the app state lock.
+pub fn persist<'a>(running: impl IntoIterator<Item = (u32, &'a Path)>) {
+    let tracked: Vec<TrackedProcess> = running
+        .into_iter()
+        .map(|(pid, executable)| TrackedProcess {
+            pid,
+            executable: executable.to_path_buf(),
+        })
+        .collect();
+
+    let writer = WRITER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || write_tracked(receiver));
+        sender
+    });
+    let _ = writer.send(tracked);
+}
+
+fn write_tracked(receiver: Receiver<Vec<TrackedProcess>>) {
+    let Some(path) = tracked_processes_path() else {
+        return;
+    };
+
+    while let Ok(mut tracked) = receiver.recv() {
+        // Only the latest set matters, so skip any superseded while writing
+        while let Ok(newer) = receiver.try_recv() {
+            tracked = newer;
+        }
+
+        if tracked.is_empty() {
+            let _ = std::fs::remove_file(&path);
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(content) = serde_json::to_string(&tracked) {
+            let _ = std::fs::write(&path, content);
+        }
+    }
+}
+
+/// Kill any reindeer that outlived a previous session. A recorded PID is only
+/// signalled if it still leads its own process group and runs the same
+/// executable, so a recycled PID belonging to something else is left alone.
+pub fn reap_survivors() {
+    let Some(path) = tracked_processes_path() else {
+        return;
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return;
+    };
+    let _ = std::fs::remove_file(&path);
+
+    let tracked: Vec<TrackedProcess> = serde_json::from_str(&content).unwrap_or_default();
+    for process in tracked {
+        if is_same_process(&process) {
+            kill_process_group(process.pid);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_same_process(process: &TrackedProcess) -> bool {
+    let pid = process.pid as libc::pid_t;
+    if unsafe { libc::getpgid(pid) } != pid {
+        return false;
+    }
+
+    match (
+        executable_of(process.pid),
+        process.executable.canonicalize(),
+    ) {
+        (Some(running), Ok(expected)) => running == expected,
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_process(_process: &TrackedProcess) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {}
+
+#[cfg(target_os = "linux")]
+fn executable_of(pid: u32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/exe", pid)).ok()
+}
+
+#[cfg(target_os = "macos")]
+fn executable_of(pid: u32) -> Option<PathBuf> {
+    let mut buffer = vec![0u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
+    let len = unsafe {
+        libc::proc_pidpath(
+            pid as libc::c_int,
+            buffer.as_mut_ptr() as *mut libc::c_void,
+            buffer.len() as u32,
+        )
+    };
+    if len <= 0 {
+        return None;
+    }
+    buffer.truncate(len as usize);
+    String::from_utf8(buffer).ok().map(PathBuf::from)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn executable_of(_pid: u32) -> Option<PathBuf> {
+    None
+}
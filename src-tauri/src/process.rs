@@ -0,0 +1,361 @@
+use crate::state::ExecutionLimits;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
+use tokio::process::{ChildStderr, ChildStdin, ChildStdout};
+use tokio::task::JoinHandle;
+
+/// How long a reindeer is given to exit after SIGTERM before it is killed
+pub const TERMINATE_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// Why a running reindeer was stopped before it finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    User,
+    /// The workbench window closed or the app quit while it was running
+    AppExit,
+    /// A watched file changed and a fresh run replaced this one
+    Superseded,
+}
+
+/// The resource limit that caused a reindeer run to be stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitExceeded {
+    Timeout,
+    CpuTime,
+    Memory,
+}
+
+impl LimitExceeded {
+    pub fn message(self) -> &'static str {
+        match self {
+            LimitExceeded::Timeout => "wall-clock timeout exceeded",
+            LimitExceeded::CpuTime => "CPU time limit exceeded",
+            LimitExceeded::Memory => "memory limit exceeded",
+        }
+    }
+}
+
+/// Why a run stopped before finishing, if it did: a resource limit, a cancel
+/// or a terminating signal. Used where there is no UI to show the completion.
+pub fn stop_message(
+    limit: Option<LimitExceeded>,
+    cancelled: Option<CancelReason>,
+    signal: Option<i32>,
+) -> Option<String> {
+    match (limit, cancelled, signal) {
+        (Some(limit), _, _) => Some(format!("Stopped: {}", limit.message())),
+        (None, Some(_), _) => Some("Cancelled".to_string()),
+        (None, None, Some(signal)) => Some(format!("Killed by signal {}", signal)),
+        (None, None, None) => None,
+    }
+}
+
+/// Resources consumed by a single reindeer run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceUsage {
+    /// Time from spawning the reindeer until it exited
+    pub wall_time_ms: f64,
+    /// CPU time spent in user mode (Unix only)
+    pub user_cpu_ms: Option<f64>,
+    /// CPU time spent in the kernel (Unix only)
+    pub system_cpu_ms: Option<f64>,
+    /// Peak resident set size (Unix only)
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl ResourceUsage {
+    /// Total CPU time in user mode and the kernel, where known
+    pub fn cpu_ms(&self) -> Option<f64> {
+        Some(self.user_cpu_ms? + self.system_cpu_ms?)
+    }
+
+    /// Whether peak memory came within 10% of a limit of `max_memory_mb`
+    #[cfg(unix)]
+    fn near_memory_limit(&self, max_memory_mb: u64) -> bool {
+        let limit_bytes = max_memory_mb.saturating_mul(1024 * 1024);
+        self.peak_rss_bytes
+            .is_some_and(|peak| peak >= limit_bytes / 10 * 9)
+    }
+}
+
+/// How a reindeer process exited and what it used
+pub struct ProcessExit {
+    pub status: ExitStatus,
+    pub usage: ResourceUsage,
+}
+
+/// A reindeer spawned by `spawn_reindeer`, with its pipes registered with
+/// tokio
+pub struct SpawnedReindeer {
+    pub pid: u32,
+    pub stdin: Option<ChildStdin>,
+    pub stdout: ChildStdout,
+    pub stderr: ChildStderr,
+    /// Resolves once the reindeer has exited and been reaped. The handle can
+    /// be awaited again after a timed-out wait.
+    pub exit: JoinHandle<std::io::Result<ProcessExit>>,
+}
+
+/// Spawn a reindeer with piped stdout and stderr, and stdin if `cmd` pipes
+/// it. It is spawned through std rather than tokio because its exit is
+/// collected with `wait4` for the rusage, and tokio would otherwise try to
+/// reap the PID again later, by which time it may belong to another process.
+pub fn spawn_reindeer(cmd: &mut Command) -> std::io::Result<SpawnedReindeer> {
+    let mut child = cmd.spawn()?;
+    let pid = child.id();
+    let pipes = async_pipes(&mut child);
+    if pipes.is_err() {
+        let _ = child.kill();
+    }
+    // Handed to the waiter even on error, so the reindeer is always reaped
+    let exit = spawn_exit_waiter(child);
+    let (stdin, stdout, stderr) = pipes?;
+    Ok(SpawnedReindeer {
+        pid,
+        stdin,
+        stdout,
+        stderr,
+        exit,
+    })
+}
+
+fn async_pipes(
+    child: &mut Child,
+) -> std::io::Result<(Option<ChildStdin>, ChildStdout, ChildStderr)> {
+    let not_piped = |stream: &str| std::io::Error::other(format!("{} was not piped", stream));
+    Ok((
+        child.stdin.take().map(ChildStdin::from_std).transpose()?,
+        ChildStdout::from_std(child.stdout.take().ok_or_else(|| not_piped("stdout"))?)?,
+        ChildStderr::from_std(child.stderr.take().ok_or_else(|| not_piped("stderr"))?)?,
+    ))
+}
+
+/// Spawn the reindeer as the leader of its own process group so that it and
+/// any launcher grandchildren can be signalled together
+pub fn isolate_process_group(cmd: &mut Command) {
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    #[cfg(windows)]
+    {
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// Ask the process group led by `pid` to exit, escalating to SIGKILL if any
+/// member is still alive once the grace period has elapsed
+#[cfg(unix)]
+pub async fn terminate_process_group(pid: u32, grace: Duration) {
+    let pgid = pid as libc::pid_t;
+
+    if !signal_process_group(pgid, libc::SIGTERM) {
+        return;
+    }
+
+    let deadline = tokio::time::Instant::now() + grace;
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        if !signal_process_group(pgid, 0) {
+            return;
+        }
+    }
+
+    signal_process_group(pgid, libc::SIGKILL);
+}
+
+#[cfg(windows)]
+pub async fn terminate_process_group(pid: u32, _grace: Duration) {
+    // Windows has no SIGTERM equivalent for console processes, so the tree is
+    // force-killed straight away
+    let _ = tokio::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .output()
+        .await;
+}
+
+/// Send `signal` to every process in the group, returning false once the
+/// group no longer exists
+#[cfg(unix)]
+fn signal_process_group(pgid: libc::pid_t, signal: libc::c_int) -> bool {
+    unsafe { libc::killpg(pgid, signal) == 0 }
+}
+
+/// Wait for the reindeer to exit on the blocking pool, collecting its rusage
+/// as it is reaped. Dropping a std `Child` neither waits nor kills, so
+/// nothing else reaps the PID.
+#[cfg(unix)]
+fn spawn_exit_waiter(child: Child) -> JoinHandle<std::io::Result<ProcessExit>> {
+    let started_at = Instant::now();
+    tokio::task::spawn_blocking(move || wait_with_rusage(child.id(), started_at))
+}
+
+#[cfg(not(unix))]
+fn spawn_exit_waiter(mut child: Child) -> JoinHandle<std::io::Result<ProcessExit>> {
+    let started_at = Instant::now();
+    tokio::task::spawn_blocking(move || {
+        let status = child.wait()?;
+        Ok(ProcessExit {
+            status,
+            usage: wall_time_only(started_at),
+        })
+    })
+}
+
+#[cfg(not(unix))]
+fn wall_time_only(started_at: Instant) -> ResourceUsage {
+    ResourceUsage {
+        wall_time_ms: started_at.elapsed().as_secs_f64() * 1000.0,
+        user_cpu_ms: None,
+        system_cpu_ms: None,
+        peak_rss_bytes: None,
+    }
+}
+
+#[cfg(unix)]
+fn wait_with_rusage(pid: u32, started_at: Instant) -> std::io::Result<ProcessExit> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut status = 0;
+    // SAFETY: rusage is plain data, fully written by a successful wait4
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        if unsafe { libc::wait4(pid as libc::pid_t, &mut status, 0, &mut rusage) } != -1 {
+            break;
+        }
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    let timeval_ms =
+        |time: libc::timeval| time.tv_sec as f64 * 1000.0 + time.tv_usec as f64 / 1000.0;
+    // ru_maxrss is in kilobytes everywhere but macOS, which reports bytes
+    let peak_rss_bytes = if cfg!(target_os = "macos") {
+        rusage.ru_maxrss as u64
+    } else {
+        rusage.ru_maxrss as u64 * 1024
+    };
+
+    Ok(ProcessExit {
+        status: ExitStatus::from_raw(status),
+        usage: ResourceUsage {
+            wall_time_ms: started_at.elapsed().as_secs_f64() * 1000.0,
+            user_cpu_ms: Some(timeval_ms(rusage.ru_utime)),
+            system_cpu_ms: Some(timeval_ms(rusage.ru_stime)),
+            peak_rss_bytes: Some(peak_rss_bytes),
+        },
+    })
+}
+
+/// Apply CPU-time and memory limits to the spawned reindeer via rlimits.
+/// The wall-clock timeout is enforced by the caller, as it has no rlimit.
+#[cfg(unix)]
+pub fn apply_resource_limits(cmd: &mut Command, limits: &ExecutionLimits) {
+    let cpu_time_secs = limits.cpu_time_secs;
+    let max_memory_bytes = limits
+        .max_memory_mb
+        .map(|mb| mb.saturating_mul(1024 * 1024));
+
+    if cpu_time_secs.is_none() && max_memory_bytes.is_none() {
+        return;
+    }
+
+    // SAFETY: the closure only calls setrlimit, which is async-signal-safe
+    unsafe {
+        cmd.pre_exec(move || {
+            if let Some(secs) = cpu_time_secs {
+                // Soft limit raises SIGXCPU, the hard limit a second later guarantees termination
+                set_rlimit(libc::RLIMIT_CPU, secs, secs.saturating_add(1))?;
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(bytes) = max_memory_bytes {
+                set_rlimit(libc::RLIMIT_AS, bytes, bytes)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply_resource_limits(_cmd: &mut Command, _limits: &ExecutionLimits) {}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+#[cfg(unix)]
+fn set_rlimit(resource: RlimitResource, soft: u64, hard: u64) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: soft as libc::rlim_t,
+        rlim_max: hard as libc::rlim_t,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Work out whether an exit was caused by one of the configured rlimits.
+/// A fatal signal alone is not enough, as the OOM killer or a manual `kill`
+/// send the same ones, so the CPU time and peak memory used are checked too.
+/// Memory exhaustion has no dedicated signal, so a failed allocation reported
+/// on stderr also counts.
+pub fn detect_limit_exceeded(
+    status: &ExitStatus,
+    usage: &ResourceUsage,
+    limits: &ExecutionLimits,
+    reported_out_of_memory: bool,
+) -> Option<LimitExceeded> {
+    if status.success() {
+        return None;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        let signal = status.signal();
+        if let Some(secs) = limits.cpu_time_secs
+            && matches!(signal, Some(libc::SIGXCPU) | Some(libc::SIGKILL))
+            && usage
+                .cpu_ms()
+                .is_some_and(|cpu_ms| cpu_ms >= secs as f64 * 1000.0)
+        {
+            return Some(LimitExceeded::CpuTime);
+        }
+        if let Some(mb) = limits.max_memory_mb
+            && (reported_out_of_memory || (signal.is_some() && usage.near_memory_limit(mb)))
+        {
+            return Some(LimitExceeded::Memory);
+        }
+    }
+
+    // Without signals only the stderr heuristic is available
+    #[cfg(not(unix))]
+    let _ = usage;
+    #[cfg(not(unix))]
+    if limits.max_memory_mb.is_some() && reported_out_of_memory {
+        return Some(LimitExceeded::Memory);
+    }
+
+    None
+}
+
+/// Whether a stderr line looks like an allocation failure
+pub fn is_out_of_memory_message(line: &str) -> bool {
+    let line = line.to_lowercase();
+    line.contains("memory allocation")
+        || line.contains("out of memory")
+        || line.contains("cannot allocate memory")
+}
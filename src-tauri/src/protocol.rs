@@ -0,0 +1,167 @@
+use crate::benchmark::BenchmarkSummary;
+use crate::process::{CancelReason, LimitExceeded, ResourceUsage};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Bumped whenever an existing event payload changes shape
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Name of the Tauri event that carries every `ReplEvent`
+pub const REPL_EVENT: &str = "repl-event";
+
+/// An event sent on an execution's channel while a reindeer is running
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExecutionEvent {
+    pub protocol_version: u32,
+    pub execution_id: String,
+    #[serde(flatten)]
+    pub payload: ExecutionPayload,
+}
+
+impl ExecutionEvent {
+    pub fn new(execution_id: impl Into<String>, payload: ExecutionPayload) -> Self {
+        ExecutionEvent {
+            protocol_version: PROTOCOL_VERSION,
+            execution_id: execution_id.into(),
+            payload,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "event_type", content = "data", rename_all = "snake_case")]
+pub enum ExecutionPayload {
+    Queued(QueuePosition),
+    /// The reindeer's initial result state
+    Initial(serde_json::Value),
+    /// An RFC 6902 JSON Patch against the current result state
+    Patch(serde_json::Value),
+    Console(ConsoleFrame),
+    Stderr(StderrOutput),
+    Truncated(ConsoleTruncated),
+    Benchmark(BenchmarkSummary),
+    Metrics(ResourceSample),
+    Complete(Completion),
+    Error(ExecutionError),
+}
+
+/// Where a waiting execution sits in the scheduler queue
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct QueuePosition {
+    /// 1-based; 1 means it starts next
+    pub position: usize,
+}
+
+/// A position in the solution source
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct Location {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Lines printed by the solution via puts(), batched so output-heavy
+/// solutions do not flood the frontend with one event per line
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConsoleFrame {
+    pub lines: Vec<String>,
+}
+
+/// Console output hit the configured limits, so the rest is written to a log
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConsoleTruncated {
+    /// Log holding the complete output, unless it could not be created
+    pub log_path: Option<String>,
+}
+
+/// A line written by the reindeer to stderr
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct StderrOutput {
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+/// Live resource usage of a running reindeer and any processes it spawned
+/// (Linux only)
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct ResourceSample {
+    /// Time since sampling started
+    pub elapsed_ms: u64,
+    pub rss_bytes: u64,
+    /// CPU use since the previous sample, where 100 is one full core
+    pub cpu_percent: f64,
+    pub processes: u32,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Completion {
+    /// Process exit code, or -1 if it was terminated by a signal
+    pub exit_code: i32,
+    /// Signal that terminated the reindeer (Unix only)
+    pub signal: Option<i32>,
+    /// Wall-clock time of the whole execution
+    pub duration_ms: u64,
+    /// Full command line, only reported in debug mode
+    pub command: Option<String>,
+    pub limit: Option<LimitExceeded>,
+    pub cancelled: Option<CancelReason>,
+    /// What the reindeer used, from its final run in benchmark mode
+    pub usage: Option<ResourceUsage>,
+    /// The result was replayed from the result cache rather than run, with
+    /// the timings and usage of the original run
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExecutionError {
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+impl ExecutionError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ExecutionError {
+            message: message.into(),
+            location: None,
+        }
+    }
+}
+
+/// An event emitted by an interactive REPL session
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReplEvent {
+    pub protocol_version: u32,
+    pub session_id: String,
+    #[serde(flatten)]
+    pub payload: ReplPayload,
+}
+
+impl ReplEvent {
+    pub fn new(session_id: impl Into<String>, payload: ReplPayload) -> Self {
+        ReplEvent {
+            protocol_version: PROTOCOL_VERSION,
+            session_id: session_id.into(),
+            payload,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "event_type", content = "data", rename_all = "snake_case")]
+pub enum ReplPayload {
+    Stdout(ReplOutput),
+    Stderr(ReplOutput),
+    Closed(ReplClosed),
+}
+
+/// Output from the REPL as it arrives, not split into lines, so prompts
+/// without a trailing newline are shown straight away
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReplOutput {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReplClosed {
+    /// Process exit code, or -1 if it was terminated by a signal
+    pub exit_code: i32,
+}
@@ -0,0 +1,64 @@
+use crate::protocol::ExecutionEvent;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Writes every event of an execution to a JSON Lines file, each stamped with
+/// the time since recording started. Lines are written as they happen so a
+/// recording survives the workbench crashing mid-run.
+pub struct Recorder {
+    started_at: Instant,
+    file: Mutex<std::fs::File>,
+}
+
+#[derive(Serialize)]
+struct RecordedEventRef<'a> {
+    elapsed_ms: u64,
+    event: &'a ExecutionEvent,
+}
+
+/// A line of a recording. The event is kept as raw JSON so recordings made by
+/// other protocol versions can still be replayed as-is.
+#[derive(Debug, Deserialize)]
+pub struct RecordedEvent {
+    pub elapsed_ms: u64,
+    pub event: serde_json::Value,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Recorder {
+            started_at: Instant::now(),
+            file: Mutex::new(std::fs::File::create(path)?),
+        })
+    }
+
+    /// Append an event, ignoring write errors
+    pub fn record(&self, event: &ExecutionEvent) {
+        let line = RecordedEventRef {
+            elapsed_ms: self.started_at.elapsed().as_millis() as u64,
+            event,
+        };
+        let Ok(mut line) = serde_json::to_string(&line) else {
+            return;
+        };
+        line.push('\n');
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+/// Read a recording, skipping lines that are not recorded events
+pub async fn read(path: &Path) -> Result<Vec<RecordedEvent>, String> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read recording: {}", e))?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
@@ -0,0 +1,92 @@
+use crate::config;
+use crate::process::ResourceUsage;
+use crate::state::Reindeer;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// The final state of a successful run, stored so an identical run can be
+/// answered without starting the reindeer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResult {
+    pub result: serde_json::Value,
+    pub exit_code: i32,
+    /// Wall-clock time of the original run
+    pub duration_ms: u64,
+    pub usage: Option<ResourceUsage>,
+}
+
+/// Everything that determines a run's result. Files the solution reads
+/// itself, other than through `aoc://` URLs, are not covered.
+pub struct CacheKey<'a> {
+    pub source: &'a str,
+    /// Input delivered to the reindeer, explicit or copied from the saved
+    /// `.input` file
+    pub input: Option<&'a str>,
+    pub reindeer: &'a Reindeer,
+    pub mode: &'a str,
+    pub test_indices: Option<&'a [usize]>,
+}
+
+impl CacheKey<'_> {
+    fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        // Length-prefix each part so different splits cannot collide
+        let mut part = |bytes: &[u8]| {
+            hasher.update((bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        };
+        part(self.source.as_bytes());
+        part(self.input.unwrap_or_default().as_bytes());
+        part(&[self.input.is_some() as u8]);
+        part(self.reindeer.codename.as_bytes());
+        part(self.reindeer.version.as_bytes());
+        part(self.mode.as_bytes());
+        let indices = self.test_indices.map(|indices| {
+            indices
+                .iter()
+                .map(|index| index.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        });
+        part(indices.unwrap_or_default().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+fn cache_dir() -> Result<PathBuf, String> {
+    Ok(config::app_data_dir()?.join("result-cache"))
+}
+
+fn entry_path(key: &CacheKey) -> Result<PathBuf, String> {
+    Ok(cache_dir()?.join(format!("{}.json", key.digest())))
+}
+
+/// Look up a stored result, treating unreadable entries as misses
+pub async fn load(key: &CacheKey<'_>) -> Option<CachedResult> {
+    let content = tokio::fs::read_to_string(entry_path(key).ok()?)
+        .await
+        .ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub async fn store(key: &CacheKey<'_>, entry: &CachedResult) -> Result<(), String> {
+    let path = entry_path(key)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn clear() -> Result<(), String> {
+    match tokio::fs::remove_dir_all(cache_dir()?).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+/// Admits executions in FIFO order while keeping the number running at once
+/// within the configured parallelism, so concurrent runs do not fight over
+/// cores and skew each other's timings
+#[derive(Default)]
+pub struct ExecutionScheduler {
+    queue: Mutex<QueueState>,
+    changed: Notify,
+}
+
+#[derive(Default)]
+struct QueueState {
+    running: usize,
+    waiting: VecDeque<Waiter>,
+    next_ticket: u64,
+}
+
+impl QueueState {
+    fn position(&self, ticket: u64) -> Option<usize> {
+        self.waiting
+            .iter()
+            .position(|waiter| waiter.ticket == ticket)
+    }
+}
+
+/// An execution waiting for a slot. The ticket keeps entries distinct even if
+/// the same execution ID is queued twice.
+struct Waiter {
+    ticket: u64,
+    execution_id: String,
+    cancelled: bool,
+}
+
+/// A running slot, released when dropped
+pub struct SchedulerPermit<'a> {
+    scheduler: &'a ExecutionScheduler,
+}
+
+impl Drop for SchedulerPermit<'_> {
+    fn drop(&mut self) {
+        self.scheduler.queue().running -= 1;
+        self.scheduler.changed.notify_waiters();
+    }
+}
+
+/// Removes a waiter from the queue when `acquire` finishes or is dropped, so
+/// an abandoned wait cannot block the executions behind it
+struct QueueEntry<'a> {
+    scheduler: &'a ExecutionScheduler,
+    ticket: u64,
+}
+
+impl Drop for QueueEntry<'_> {
+    fn drop(&mut self) {
+        let mut queue = self.scheduler.queue();
+        if let Some(position) = queue.position(self.ticket) {
+            queue.waiting.remove(position);
+            self.scheduler.changed.notify_waiters();
+        }
+    }
+}
+
+impl ExecutionScheduler {
+    fn queue(&self) -> MutexGuard<'_, QueueState> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait for a running slot. `on_queued` is called with the 1-based queue
+    /// position whenever it changes while waiting. Returns None if the
+    /// execution was cancelled before it could start.
+    pub async fn acquire(
+        &self,
+        execution_id: &str,
+        max_parallel: usize,
+        mut on_queued: impl FnMut(usize),
+    ) -> Option<SchedulerPermit<'_>> {
+        let entry = {
+            let mut queue = self.queue();
+            let ticket = queue.next_ticket;
+            queue.next_ticket += 1;
+            queue.waiting.push_back(Waiter {
+                ticket,
+                execution_id: execution_id.to_string(),
+                cancelled: false,
+            });
+            QueueEntry {
+                scheduler: self,
+                ticket,
+            }
+        };
+        let mut last_position = None;
+
+        loop {
+            // Register for wake-ups before inspecting the queue so a release
+            // between the check and the await is not missed
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            {
+                let mut queue = self.queue();
+                let position = queue.position(entry.ticket)?;
+
+                // Dropping the entry withdraws a cancelled waiter
+                if queue.waiting[position].cancelled {
+                    return None;
+                }
+
+                if position == 0 && queue.running < max_parallel.max(1) {
+                    queue.waiting.pop_front();
+                    queue.running += 1;
+                    self.changed.notify_waiters();
+                    return Some(SchedulerPermit { scheduler: self });
+                }
+
+                if last_position != Some(position + 1) {
+                    last_position = Some(position + 1);
+                    on_queued(position + 1);
+                }
+            }
+
+            changed.await;
+        }
+    }
+
+    /// Withdraw a queued execution, returning false if it is not waiting
+    pub fn cancel(&self, execution_id: &str) -> bool {
+        let mut queue = self.queue();
+        let mut found = false;
+        for waiter in queue
+            .waiting
+            .iter_mut()
+            .filter(|waiter| waiter.execution_id == execution_id)
+        {
+            waiter.cancelled = true;
+            found = true;
+        }
+        if found {
+            self.changed.notify_waiters();
+        }
+        found
+    }
+}
+
+/// Default parallelism when none is configured: one execution per core
+pub fn default_max_parallel() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
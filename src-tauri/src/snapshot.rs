@@ -0,0 +1,76 @@
+use crate::process::{CancelReason, LimitExceeded};
+use crate::state::Reindeer;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The authoritative result of an execution, built by applying the reindeer's
+/// RFC 6902 patches to its initial state
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ExecutionSnapshot {
+    pub execution_id: String,
+    pub mode: String,
+    /// The reindeer the execution ran on
+    pub reindeer: Option<Reindeer>,
+    pub complete: bool,
+    pub exit_code: Option<i32>,
+    /// Wall-clock time of the whole execution, once complete
+    pub duration_ms: Option<u64>,
+    /// Signal that terminated the reindeer (Unix only)
+    pub signal: Option<i32>,
+    /// Resource limit the reindeer was stopped for
+    pub limit: Option<LimitExceeded>,
+    /// Set if the execution was stopped before it finished
+    pub cancelled: Option<CancelReason>,
+    /// Current `SolutionExecutionState`/`TestExecutionState`/`ScriptExecutionState`
+    pub result: Option<serde_json::Value>,
+    pub patches_applied: usize,
+}
+
+/// Materializes a JSONL result stream: the first protocol line is the
+/// initial state and each later line is a JSON Patch against it
+#[derive(Debug, Clone, Default)]
+pub struct ResultState {
+    state: Option<serde_json::Value>,
+    patches_applied: usize,
+}
+
+impl ResultState {
+    pub fn is_initialized(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn state(&self) -> Option<&serde_json::Value> {
+        self.state.as_ref()
+    }
+
+    pub fn into_state(self) -> Option<serde_json::Value> {
+        self.state
+    }
+
+    pub fn set_initial(&mut self, initial: serde_json::Value) -> Result<(), String> {
+        if !initial.is_object() {
+            return Err("Initial execution state must be a JSON object".to_string());
+        }
+        self.state = Some(initial);
+        self.patches_applied = 0;
+        Ok(())
+    }
+
+    /// Apply a patch atomically; on error the state is left unchanged
+    pub fn apply_patch(&mut self, patch: &serde_json::Value) -> Result<(), String> {
+        let state = self
+            .state
+            .as_mut()
+            .ok_or("Received a patch before the initial execution state")?;
+        let patch: json_patch::Patch = serde_json::from_value(patch.clone())
+            .map_err(|e| format!("Malformed JSON Patch: {}", e))?;
+        json_patch::patch(state, &patch)
+            .map_err(|e| format!("Failed to apply JSON Patch: {}", e))?;
+        self.patches_applied += 1;
+        Ok(())
+    }
+
+    pub fn patches_applied(&self) -> usize {
+        self.patches_applied
+    }
+}
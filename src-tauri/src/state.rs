@@ -1,9 +1,16 @@
+use crate::config;
+use crate::orphans;
+use crate::process::CancelReason;
+use crate::scheduler;
+use crate::snapshot::ExecutionSnapshot;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::AppHandle;
+use tokio::sync::mpsc::UnboundedSender;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Reindeer {
     pub id: String,
     pub name: String,
@@ -12,7 +19,8 @@ pub struct Reindeer {
     pub path: PathBuf,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// User preferences, saved in the app's config
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct Settings {
     pub aoc_session_token: Option<String>,
     pub default_reindeer: Option<String>,
@@ -22,22 +30,178 @@ pub struct Settings {
     pub formatter_path: Option<PathBuf>,
     #[serde(default)]
     pub debug_mode: bool,
+    #[serde(default)]
+    pub execution_limits: ExecutionLimits,
+    #[serde(default)]
+    pub console_limits: ConsoleLimits,
+    /// Maximum executions running at once (defaults to the number of cores)
+    #[serde(default)]
+    pub max_parallel_executions: Option<usize>,
+    /// Run queued executions strictly one at a time for reliable timings
+    #[serde(default)]
+    pub exclusive_benchmarking: bool,
+    /// Interval for sampling the memory and CPU use of running reindeer
+    /// (sampling is off when unset)
+    #[serde(default)]
+    pub metrics_interval_ms: Option<u64>,
+    /// Answer unchanged runs from the result cache instead of running them
+    #[serde(default)]
+    pub result_cache: bool,
+}
+
+impl Settings {
+    pub fn effective_max_parallel(&self) -> usize {
+        if self.exclusive_benchmarking {
+            return 1;
+        }
+        self.max_parallel_executions
+            .unwrap_or_else(scheduler::default_max_parallel)
+            .max(1)
+    }
+}
+
+/// Resource limits applied to each reindeer run (unset means unlimited)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, JsonSchema)]
+pub struct ExecutionLimits {
+    pub timeout_secs: Option<u64>,
+    pub cpu_time_secs: Option<u64>,
+    pub max_memory_mb: Option<u64>,
+}
+
+impl ExecutionLimits {
+    /// Combine with per-call overrides, which can replace or remove each limit
+    pub fn with_overrides(self, overrides: Option<LimitOverrides>) -> Self {
+        let Some(overrides) = overrides else {
+            return self;
+        };
+        ExecutionLimits {
+            timeout_secs: LimitOverride::apply(overrides.timeout_secs, self.timeout_secs),
+            cpu_time_secs: LimitOverride::apply(overrides.cpu_time_secs, self.cpu_time_secs),
+            max_memory_mb: LimitOverride::apply(overrides.max_memory_mb, self.max_memory_mb),
+        }
+    }
+}
+
+/// Per-call changes to the configured `ExecutionLimits` (unset keeps the
+/// configured limit)
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct LimitOverrides {
+    pub timeout_secs: Option<LimitOverride>,
+    pub cpu_time_secs: Option<LimitOverride>,
+    pub max_memory_mb: Option<LimitOverride>,
+}
+
+/// A limit to use in place of the configured one, or none at all
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum LimitOverride {
+    Limit(u64),
+    Unlimited(NoLimit),
+}
+
+/// The `"unlimited"` override, removing a configured limit
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NoLimit {
+    Unlimited,
+}
+
+impl LimitOverride {
+    fn apply(overridden: Option<Self>, configured: Option<u64>) -> Option<u64> {
+        match overridden {
+            None => configured,
+            Some(LimitOverride::Limit(limit)) => Some(limit),
+            Some(LimitOverride::Unlimited(_)) => None,
+        }
+    }
+}
+
+/// Caps on console output forwarded to the frontend per run (unset means
+/// unlimited). Output past either cap is written to a log file instead.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct ConsoleLimits {
+    pub max_lines: Option<usize>,
+    pub max_bytes: Option<usize>,
+}
+
+impl Default for ConsoleLimits {
+    fn default() -> Self {
+        ConsoleLimits {
+            max_lines: Some(10_000),
+            max_bytes: Some(1024 * 1024),
+        }
+    }
+}
+
+impl ConsoleLimits {
+    pub fn is_limited(&self) -> bool {
+        self.max_lines.is_some() || self.max_bytes.is_some()
+    }
 }
 
+const MAX_RECENT_WORKING_DIRS: usize = 20;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppState {
     pub reindeer: HashMap<String, Reindeer>,
     pub settings: Settings,
+    /// Directories solutions have been run from, most recent first
+    #[serde(default)]
+    pub recent_working_dirs: Vec<PathBuf>,
+    #[serde(skip)]
+    pub running_processes: HashMap<String, RunningProcess>,
+    /// Executions in progress, with the reason each was asked to stop. A
+    /// cancel is recorded here at any stage of the execution and checked
+    /// before every reindeer is spawned.
+    #[serde(skip)]
+    pub active_executions: HashMap<String, Option<CancelReason>>,
+    #[serde(skip)]
+    pub execution_snapshots: HashMap<String, ExecutionSnapshot>,
     #[serde(skip)]
-    pub running_processes: HashMap<String, u32>,
+    pub repl_sessions: HashMap<String, ReplSession>,
+    /// Set when run by the CLI, which stops its own reindeer on exit and must
+    /// not overwrite the app's record of running processes
+    #[serde(skip)]
+    pub headless: bool,
+}
+
+/// A spawned reindeer, tracked by execution_id until it exits
+#[derive(Debug, Clone)]
+pub struct RunningProcess {
+    /// Process ID of the reindeer, which is also its process group ID
+    pub pid: u32,
+    pub executable: PathBuf,
+    pub tab_id: Option<String>,
+    pub cancel_reason: Option<CancelReason>,
+}
+
+/// A reindeer running its REPL, tracked by session_id until it exits
+#[derive(Debug, Clone)]
+pub struct ReplSession {
+    /// Process ID of the reindeer, which is also its process group ID
+    pub pid: u32,
+    pub executable: PathBuf,
+    /// Lines to write to the REPL's stdin
+    pub input: UnboundedSender<String>,
 }
 
 impl AppState {
     pub fn load(app: &AppHandle) -> Result<Self, Box<dyn std::error::Error>> {
-        let config_path = Self::config_path(app)?;
+        Self::load_from(&Self::config_path(app)?)
+    }
+
+    /// Load the app's config without a running app, as the CLI does. The
+    /// state is marked headless.
+    pub fn load_headless() -> Result<Self, Box<dyn std::error::Error>> {
+        let mut state = Self::load_from(&Self::config_path_for(config::APP_IDENTIFIER)?)?;
+        state.headless = true;
+        Ok(state)
+    }
 
+    fn load_from(config_path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
         if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
+            let content = std::fs::read_to_string(config_path)?;
             let state: AppState = serde_json::from_str(&content)?;
             Ok(state)
         } else {
@@ -63,9 +227,78 @@ impl AppState {
         Ok(())
     }
 
+    /// Start tracking a spawned reindeer, persisting its PID so it can be
+    /// cleaned up if the workbench crashes
+    pub fn track_process(&mut self, execution_id: String, process: RunningProcess) {
+        self.running_processes.insert(execution_id, process);
+        self.persist_tracked_processes();
+    }
+
+    pub fn untrack_process(&mut self, execution_id: &str) -> Option<RunningProcess> {
+        let process = self.running_processes.remove(execution_id)?;
+        self.persist_tracked_processes();
+        Some(process)
+    }
+
+    pub fn track_repl_session(&mut self, session_id: String, session: ReplSession) {
+        self.repl_sessions.insert(session_id, session);
+        self.persist_tracked_processes();
+    }
+
+    pub fn untrack_repl_session(&mut self, session_id: &str) -> Option<ReplSession> {
+        let session = self.repl_sessions.remove(session_id)?;
+        self.persist_tracked_processes();
+        Some(session)
+    }
+
+    /// The reason an execution in progress was asked to stop, if it was
+    pub fn cancel_requested(&self, execution_id: &str) -> Option<CancelReason> {
+        self.active_executions.get(execution_id).copied().flatten()
+    }
+
+    /// PIDs of every spawned reindeer, whether running a solution or a REPL
+    pub fn tracked_pids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.running_processes
+            .values()
+            .map(|process| process.pid)
+            .chain(self.repl_sessions.values().map(|session| session.pid))
+    }
+
+    fn persist_tracked_processes(&self) {
+        if self.headless {
+            return;
+        }
+        orphans::persist(
+            self.running_processes
+                .values()
+                .map(|process| (process.pid, process.executable.as_path()))
+                .chain(
+                    self.repl_sessions
+                        .values()
+                        .map(|session| (session.pid, session.executable.as_path())),
+                ),
+        );
+    }
+
+    /// Record a working directory, returning true if it was not already known
+    pub fn remember_working_dir(&mut self, dir: PathBuf) -> bool {
+        if let Some(index) = self.recent_working_dirs.iter().position(|d| *d == dir) {
+            let dir = self.recent_working_dirs.remove(index);
+            self.recent_working_dirs.insert(0, dir);
+            return false;
+        }
+
+        self.recent_working_dirs.insert(0, dir);
+        self.recent_working_dirs.truncate(MAX_RECENT_WORKING_DIRS);
+        true
+    }
+
     fn config_path(app: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Self::config_path_for(&app.config().identifier)
+    }
+
+    fn config_path_for(app_id: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
         let config_dir = dirs::config_dir().ok_or("Could not find config directory")?;
-        let app_id = app.config().identifier.clone();
         Ok(config_dir.join(app_id).join("config.json"))
     }
 }
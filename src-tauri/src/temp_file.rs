@@ -0,0 +1,79 @@
+use crate::input;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const PREFIX: &str = ".santa-workbench-";
+const EXTENSION: &str = ".santa";
+const INPUT_EXTENSION: &str = ".input";
+
+/// How old a temporary file must be before a sweep removes it, so files of
+/// runs still in progress in another instance or the CLI are left alone
+const STALE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A solution written to disk for a reindeer to run, optionally with its
+/// puzzle input as a sibling `.input` file. The files are removed when the
+/// guard is dropped, including on early returns and panics.
+pub struct TempSolutionFile {
+    path: PathBuf,
+    input_path: Option<PathBuf>,
+}
+
+impl TempSolutionFile {
+    pub async fn create(dir: &Path, source: &str, input: Option<&str>) -> std::io::Result<Self> {
+        let mut file = TempSolutionFile {
+            path: dir.join(format!("{}{}{}", PREFIX, uuid::Uuid::new_v4(), EXTENSION)),
+            input_path: None,
+        };
+        tokio::fs::write(&file.path, source).await?;
+
+        if let Some(input) = input {
+            let input_path = input::sibling_input_path(&file.path);
+            // Track the path before writing so a partial file is still removed
+            file.input_path = Some(input_path.clone());
+            tokio::fs::write(&input_path, input).await?;
+        }
+
+        Ok(file)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempSolutionFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+        if let Some(input_path) = &self.input_path {
+            let _ = std::fs::remove_file(input_path);
+        }
+    }
+}
+
+fn is_temp_solution_file(name: &str) -> bool {
+    name.starts_with(PREFIX) && (name.ends_with(EXTENSION) || name.ends_with(INPUT_EXTENSION))
+}
+
+/// Remove temporary solution and input files left behind by a crash. Only run at
+/// startup, before any execution can begin.
+pub fn sweep_stale(dirs: impl IntoIterator<Item = PathBuf>) {
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let is_stale = is_temp_solution_file(&entry.file_name().to_string_lossy())
+                && entry
+                    .metadata()
+                    .ok()
+                    .filter(|metadata| metadata.is_file())
+                    .and_then(|metadata| metadata.modified().ok())
+                    .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                    .is_some_and(|age| age > STALE_AFTER);
+            if is_stale {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use crate::state::Reindeer;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Flag a reindeer advertises in `--help` when it can run a subset of tests
+/// itself, taking comma-separated 0-based test indices
+pub const TEST_FILTER_FLAG: &str = "--test-filter";
+
+/// How long a reindeer may take to print its help before it is assumed not
+/// to support filtering
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Probe results by reindeer path and version, so each is only probed once
+static FILTER_SUPPORT: LazyLock<Mutex<HashMap<(PathBuf, String), bool>>> =
+    LazyLock::new(Default::default);
+
+/// Whether the reindeer lists `TEST_FILTER_FLAG` in its help output
+pub async fn reindeer_supports_filter(reindeer: &Reindeer) -> bool {
+    let key = (reindeer.path.clone(), reindeer.version.clone());
+    if let Some(supported) = FILTER_SUPPORT
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&key).copied())
+    {
+        return supported;
+    }
+
+    let mut cmd = Command::new(&reindeer.path);
+    cmd.arg("--help").kill_on_drop(true);
+    let supported = match tokio::time::timeout(PROBE_TIMEOUT, cmd.output()).await {
+        Ok(Ok(output)) => {
+            String::from_utf8_lossy(&output.stdout).contains(TEST_FILTER_FLAG)
+                || String::from_utf8_lossy(&output.stderr).contains(TEST_FILTER_FLAG)
+        }
+        Ok(Err(_)) | Err(_) => false,
+    };
+
+    if let Ok(mut cache) = FILTER_SUPPORT.lock() {
+        cache.insert(key, supported);
+    }
+    supported
+}
+
+/// Rewrite `source` so only the selected `test:` blocks remain. Removed blocks
+/// keep their line breaks so reported line numbers still match the editor.
+/// `indices` are 0-based positions of the blocks in the source.
+pub fn select_tests(source: &str, indices: &[usize]) -> Result<(String, TestIndexMap), String> {
+    if indices.is_empty() {
+        return Err("No tests selected".to_string());
+    }
+
+    let blocks = find_test_blocks(source);
+    if let Some(missing) = indices.iter().find(|&&index| index >= blocks.len()) {
+        return Err(format!("Test {} does not exist", missing));
+    }
+
+    let mut rewritten = String::with_capacity(source.len());
+    let mut copied_up_to = 0;
+    let mut kept = Vec::new();
+
+    for (index, block) in blocks.into_iter().enumerate() {
+        if indices.contains(&index) {
+            kept.push(index);
+            continue;
+        }
+        rewritten.push_str(&source[copied_up_to..block.start]);
+        rewritten.extend(source[block.clone()].chars().filter(|&c| c == '\n'));
+        copied_up_to = block.end;
+    }
+    rewritten.push_str(&source[copied_up_to..]);
+
+    Ok((rewritten, TestIndexMap { original: kept }))
+}
+
+/// Maps the tests a reindeer reports for rewritten source back to their
+/// positions in the original source
+#[derive(Debug, Clone)]
+pub struct TestIndexMap {
+    /// Original index of each remaining test, in order
+    original: Vec<usize>,
+}
+
+impl TestIndexMap {
+    /// Remap the `tests` of an initial result state
+    pub fn remap_initial(&self, state: &mut Value) {
+        if let Some(tests) = state.get_mut("tests").and_then(Value::as_array_mut) {
+            for (position, test) in tests.iter_mut().enumerate() {
+                self.remap_test(position, test);
+            }
+        }
+    }
+
+    /// Remap whole test objects added or replaced by a JSON Patch
+    pub fn remap_patch(&self, patch: &mut Value) {
+        let Some(operations) = patch.as_array_mut() else {
+            return;
+        };
+
+        for operation in operations {
+            let position = operation
+                .get("path")
+                .and_then(Value::as_str)
+                .and_then(|path| path.strip_prefix("/tests/"))
+                .and_then(|position| position.parse::<usize>().ok());
+            if let Some(position) = position
+                && let Some(test) = operation.get_mut("value")
+            {
+                self.remap_test(position, test);
+            }
+        }
+    }
+
+    /// Rewrite a test's `index`, keeping whatever numbering base the
+    /// reindeer uses
+    fn remap_test(&self, position: usize, test: &mut Value) {
+        let Some(&original) = self.original.get(position) else {
+            return;
+        };
+        if let Some(index) = test.get_mut("index")
+            && let Some(reported) = index.as_u64()
+        {
+            let base = reported.saturating_sub(position as u64);
+            *index = Value::from(base + original as u64);
+        }
+    }
+}
+
+/// Byte ranges of top-level `test: { ... }` sections, including any `@slow`
+/// attribute in front of them
+fn find_test_blocks(source: &str) -> Vec<Range<usize>> {
+    let bytes = source.as_bytes();
+    let mut blocks = Vec::new();
+    let mut depth = 0usize;
+    let mut attribute_start = None;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i = skip_string(bytes, i);
+                attribute_start = None;
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = skip_line(bytes, i);
+                continue;
+            }
+            b'@' if depth == 0 && is_word_at(source, i + 1, "slow") => {
+                attribute_start = Some(i);
+                i += "@slow".len();
+                continue;
+            }
+            b't' if depth == 0
+                && is_word_at(source, i, "test")
+                && let Some(open) = test_body_start(source, i + "test".len())
+                && let Some(end) = block_end(bytes, open) =>
+            {
+                blocks.push(attribute_start.take().unwrap_or(i)..end);
+                i = end;
+                continue;
+            }
+            b'{' | b'[' | b'(' => depth += 1,
+            b'}' | b']' | b')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if !bytes[i].is_ascii_whitespace() {
+            attribute_start = None;
+        }
+        i += 1;
+    }
+
+    blocks
+}
+
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// Whether `word` appears at `at` as a whole identifier
+fn is_word_at(source: &str, at: usize, word: &str) -> bool {
+    let bytes = source.as_bytes();
+    source[at.min(source.len())..].starts_with(word)
+        && (at == 0 || bytes[at - 1] == b'@' || !is_identifier_byte(bytes[at - 1]))
+        && bytes
+            .get(at + word.len())
+            .is_none_or(|&next| !is_identifier_byte(next))
+}
+
+/// Position of the `{` in `: {` following a section label, if present
+fn test_body_start(source: &str, after_label: usize) -> Option<usize> {
+    let rest = &source[after_label..];
+    let colon = rest.len() - rest.trim_start().len();
+    let rest = rest.trim_start().strip_prefix(':')?;
+    let brace = rest.len() - rest.trim_start().len();
+    rest.trim_start()
+        .starts_with('{')
+        .then_some(after_label + colon + 1 + brace)
+}
+
+/// Position just past the brace closing the one at `open`
+fn block_end(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut i = open;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i = skip_string(bytes, i);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = skip_line(bytes, i);
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Position just past the string literal starting at `start`
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Position of the line break ending the comment starting at `start`
+fn skip_line(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(bytes.len(), |offset| start + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(source: &str) -> Vec<&str> {
+        find_test_blocks(source)
+            .into_iter()
+            .map(|block| &source[block])
+            .collect()
+    }
+
+    #[test]
+    fn finds_top_level_test_sections() {
+        let source = "part_one: { |input| input }\n\ntest: {\n  input: \"1\"\n  part_one: 1\n}\n\ntest: { input: \"2\", part_one: 2 }\n";
+
+        assert_eq!(
+            blocks(source),
+            [
+                "test: {\n  input: \"1\"\n  part_one: 1\n}",
+                "test: { input: \"2\", part_one: 2 }"
+            ]
+        );
+    }
+
+    #[test]
+    fn includes_slow_attribute_in_block() {
+        let source = "test: { part_one: 1 }\n\n@slow\ntest: { part_one: 2 }\n";
+
+        assert_eq!(
+            blocks(source),
+            ["test: { part_one: 1 }", "@slow\ntest: { part_one: 2 }"]
+        );
+    }
+
+    #[test]
+    fn ignores_test_sections_inside_strings() {
+        let source =
+            "let label = \"test: {\";\ntest: {\n  input: \"test: { }\"\n  part_one: \"}\"\n}\n";
+
+        assert_eq!(
+            blocks(source),
+            ["test: {\n  input: \"test: { }\"\n  part_one: \"}\"\n}"]
+        );
+    }
+
+    #[test]
+    fn ignores_test_sections_inside_comments() {
+        let source =
+            "// test: { part_one: 0 }\ntest: {\n  // closing } in a comment\n  part_one: 1\n}\n";
+
+        assert_eq!(
+            blocks(source),
+            ["test: {\n  // closing } in a comment\n  part_one: 1\n}"]
+        );
+    }
+
+    #[test]
+    fn matches_nested_braces_within_a_section() {
+        let source = "test: {\n  input: #{\"a\": {1, 2}}\n  part_one: |x| { x + 1 }\n}\ntest: { part_one: 2 }\n";
+
+        assert_eq!(
+            blocks(source),
+            [
+                "test: {\n  input: #{\"a\": {1, 2}}\n  part_one: |x| { x + 1 }\n}",
+                "test: { part_one: 2 }"
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_nested_and_prefixed_test_labels() {
+        let source = "part_one: {\n  let test = #{\"test\": 1};\n}\nmy_test: { part_one: 1 }\n";
+
+        assert!(blocks(source).is_empty());
+    }
+
+    #[test]
+    fn select_tests_blanks_unselected_blocks_keeping_lines() {
+        let source =
+            "test: {\n  part_one: 1\n}\n@slow\ntest: {\n  part_one: 2\n}\ntest: { part_one: 3 }\n";
+
+        let (rewritten, index_map) = select_tests(source, &[1]).unwrap();
+
+        assert_eq!(rewritten, "\n\n\n@slow\ntest: {\n  part_one: 2\n}\n\n");
+        assert_eq!(rewritten.lines().count(), source.lines().count());
+        assert_eq!(index_map.original, [1]);
+    }
+
+    #[test]
+    fn select_tests_rejects_empty_and_missing_selections() {
+        let source = "test: { part_one: 1 }\n";
+
+        assert!(select_tests(source, &[]).is_err());
+        assert!(select_tests(source, &[1]).is_err());
+    }
+
+    #[test]
+    fn remaps_reported_indices_to_original_positions() {
+        let index_map = TestIndexMap {
+            original: vec![2, 5],
+        };
+        let mut initial = serde_json::json!({
+            "type": "test",
+            "tests": [{ "index": 1 }, { "index": 2 }]
+        });
+        let mut patch = serde_json::json!([
+            { "op": "replace", "path": "/tests/1", "value": { "index": 2, "status": "complete" } }
+        ]);
+
+        index_map.remap_initial(&mut initial);
+        index_map.remap_patch(&mut patch);
+
+        assert_eq!(initial["tests"][0]["index"], 3);
+        assert_eq!(initial["tests"][1]["index"], 6);
+        assert_eq!(patch[0]["value"]["index"], 6);
+    }
+}
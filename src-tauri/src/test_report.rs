@@ -0,0 +1,270 @@
+use crate::state::Reindeer;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Standard formats test results can be exported in for CI report viewers
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Junit,
+    Tap,
+}
+
+/// One reindeer's materialized `TestExecutionState`
+pub struct TestSuite<'a> {
+    pub reindeer: &'a Reindeer,
+    pub result: &'a Value,
+    /// Why the run stopped before finishing, from `process::stop_message`
+    pub stopped: Option<String>,
+}
+
+enum Outcome {
+    Passed,
+    Failed {
+        expected: String,
+        actual: String,
+    },
+    /// Not run, such as a `@slow` test in a fast test run
+    Skipped,
+    /// The solution failed before its tests could finish
+    Error {
+        message: String,
+    },
+}
+
+struct TestCase {
+    name: String,
+    outcome: Outcome,
+}
+
+impl TestSuite<'_> {
+    fn name(&self) -> String {
+        format!("{} {}", self.reindeer.name, self.reindeer.version)
+    }
+
+    /// A case per part of each `test:` block, plus one for an execution error
+    fn cases(&self) -> Vec<TestCase> {
+        let mut cases = Vec::new();
+
+        for test in self.result["tests"].as_array().into_iter().flatten() {
+            let label = format!("Test {}", test["index"]);
+            let parts: Vec<(&str, &Value)> = [
+                ("part one", &test["part_one"]),
+                ("part two", &test["part_two"]),
+            ]
+            .into_iter()
+            .filter(|(_, part)| part.is_object())
+            .collect();
+
+            if parts.is_empty() {
+                cases.push(TestCase {
+                    name: label,
+                    outcome: Outcome::Skipped,
+                });
+                continue;
+            }
+
+            for (part_name, part) in parts {
+                let outcome = if part["passed"].as_bool() == Some(true) {
+                    Outcome::Passed
+                } else {
+                    Outcome::Failed {
+                        expected: value_text(&part["expected"]),
+                        actual: value_text(&part["actual"]),
+                    }
+                };
+                cases.push(TestCase {
+                    name: format!("{} {}", label, part_name),
+                    outcome,
+                });
+            }
+        }
+
+        if let Some(error) = self.result.get("error").filter(|error| error.is_object()) {
+            cases.push(TestCase {
+                name: "Execution".to_string(),
+                outcome: Outcome::Error {
+                    message: value_text(&error["message"]),
+                },
+            });
+        }
+
+        // A limit or signal can stop the run between tests, leaving no error
+        // in the result
+        if let Some(stopped) = &self.stopped {
+            cases.push(TestCase {
+                name: "Run".to_string(),
+                outcome: Outcome::Error {
+                    message: stopped.clone(),
+                },
+            });
+        }
+
+        cases
+    }
+}
+
+pub fn render(format: ReportFormat, suites: &[TestSuite]) -> String {
+    match format {
+        ReportFormat::Junit => junit(suites),
+        ReportFormat::Tap => tap(suites),
+    }
+}
+
+fn junit(suites: &[TestSuite]) -> String {
+    let suites: Vec<(&TestSuite, Vec<TestCase>)> =
+        suites.iter().map(|suite| (suite, suite.cases())).collect();
+    let count = |cases: &[TestCase], matches: fn(&Outcome) -> bool| {
+        cases.iter().filter(|case| matches(&case.outcome)).count()
+    };
+    let is_failure = |outcome: &Outcome| matches!(outcome, Outcome::Failed { .. });
+    let is_error = |outcome: &Outcome| matches!(outcome, Outcome::Error { .. });
+    let is_skipped = |outcome: &Outcome| matches!(outcome, Outcome::Skipped);
+
+    let total: usize = suites.iter().map(|(_, cases)| cases.len()).sum();
+    let failures: usize = suites
+        .iter()
+        .map(|(_, cases)| count(cases, is_failure))
+        .sum();
+    let errors: usize = suites.iter().map(|(_, cases)| count(cases, is_error)).sum();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+        total, failures, errors
+    ));
+
+    for (suite, cases) in &suites {
+        let name = xml_escape(&suite.name());
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">\n",
+            name,
+            cases.len(),
+            count(cases, is_failure),
+            count(cases, is_error),
+            count(cases, is_skipped)
+        ));
+
+        out.push_str("    <properties>\n");
+        for (property, value) in [
+            ("reindeer.name", &suite.reindeer.name),
+            ("reindeer.codename", &suite.reindeer.codename),
+            ("reindeer.version", &suite.reindeer.version),
+        ] {
+            out.push_str(&format!(
+                "      <property name=\"{}\" value=\"{}\"/>\n",
+                property,
+                xml_escape(value)
+            ));
+        }
+        out.push_str("    </properties>\n");
+
+        for case in cases {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                xml_escape(&case.name),
+                name
+            );
+            match &case.outcome {
+                Outcome::Passed => out.push_str(&format!("{}/>\n", open)),
+                Outcome::Failed { expected, actual } => out.push_str(&format!(
+                    "{}>\n      <failure message=\"{}\">Expected: {}\nActual: {}</failure>\n    </testcase>\n",
+                    open,
+                    xml_escape(&format!("Expected {}, got {}", expected, actual)),
+                    xml_escape(expected),
+                    xml_escape(actual)
+                )),
+                Outcome::Skipped => out.push_str(&format!(
+                    "{}>\n      <skipped message=\"Not run\"/>\n    </testcase>\n",
+                    open
+                )),
+                Outcome::Error { message } => out.push_str(&format!(
+                    "{}>\n      <error message=\"{}\"/>\n    </testcase>\n",
+                    open,
+                    xml_escape(message)
+                )),
+            }
+        }
+
+        out.push_str("  </testsuite>\n");
+    }
+
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// TAP version 13, with failure details as YAML diagnostics. TAP has no
+/// properties, so the reindeer of each suite is given in a comment.
+fn tap(suites: &[TestSuite]) -> String {
+    let suites: Vec<(&TestSuite, Vec<TestCase>)> =
+        suites.iter().map(|suite| (suite, suite.cases())).collect();
+    let total: usize = suites.iter().map(|(_, cases)| cases.len()).sum();
+
+    let mut out = format!("TAP version 13\n1..{}\n", total);
+    let mut number = 0;
+
+    for (suite, cases) in &suites {
+        out.push_str(&format!(
+            "# {} ({} {})\n",
+            suite.reindeer.name, suite.reindeer.codename, suite.reindeer.version
+        ));
+
+        for case in cases {
+            number += 1;
+            let name = format!("{} - {}", suite.name(), case.name);
+            match &case.outcome {
+                Outcome::Passed => out.push_str(&format!("ok {} - {}\n", number, name)),
+                Outcome::Skipped => {
+                    out.push_str(&format!("ok {} - {} # SKIP not run\n", number, name))
+                }
+                Outcome::Failed { expected, actual } => {
+                    out.push_str(&format!("not ok {} - {}\n", number, name));
+                    out.push_str(&format!(
+                        "  ---\n  expected: {}\n  actual: {}\n  ...\n",
+                        yaml_string(expected),
+                        yaml_string(actual)
+                    ));
+                }
+                Outcome::Error { message } => {
+                    out.push_str(&format!("not ok {} - {}\n", number, name));
+                    out.push_str(&format!(
+                        "  ---\n  message: {}\n  ...\n",
+                        yaml_string(message)
+                    ));
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A JSON string is also a valid double-quoted YAML scalar
+fn yaml_string(text: &str) -> String {
+    Value::from(text).to_string()
+}
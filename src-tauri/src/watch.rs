@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::async_runtime::JoinHandle;
+
+/// How often watched files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long files must stay unchanged before a change is acted upon, so an
+/// editor's save or a script regenerating input triggers a single re-run
+const DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// Active watch sessions, keyed by tab ID
+#[derive(Default)]
+pub struct WatchRegistry {
+    sessions: Mutex<HashMap<String, WatchSession>>,
+}
+
+struct WatchSession {
+    task: JoinHandle<()>,
+    /// Executions started by this session, cancelled before each re-run
+    launched: Vec<String>,
+}
+
+impl WatchRegistry {
+    /// Register a session's polling task, stopping any previous one for the tab
+    pub fn insert(&self, tab_id: String, task: JoinHandle<()>) {
+        let previous = self.sessions.lock().ok().and_then(|mut sessions| {
+            sessions.insert(
+                tab_id,
+                WatchSession {
+                    task,
+                    launched: Vec::new(),
+                },
+            )
+        });
+        if let Some(previous) = previous {
+            previous.task.abort();
+        }
+    }
+
+    pub fn remove(&self, tab_id: &str) -> bool {
+        let session = self
+            .sessions
+            .lock()
+            .ok()
+            .and_then(|mut sessions| sessions.remove(tab_id));
+        match session {
+            Some(session) => {
+                session.task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop every session, so no re-run starts while the app shuts down
+    pub fn clear(&self) {
+        let sessions = self
+            .sessions
+            .lock()
+            .map(|mut sessions| std::mem::take(&mut *sessions))
+            .unwrap_or_default();
+        for session in sessions.into_values() {
+            session.task.abort();
+        }
+    }
+
+    /// Replace the executions recorded for a tab, returning the previous ones
+    pub fn replace_launched(&self, tab_id: &str, launched: Vec<String>) -> Vec<String> {
+        self.sessions
+            .lock()
+            .ok()
+            .and_then(|mut sessions| {
+                sessions
+                    .get_mut(tab_id)
+                    .map(|session| std::mem::replace(&mut session.launched, launched))
+            })
+            .unwrap_or_default()
+    }
+}
+
+type Fingerprint = Vec<Option<(SystemTime, u64)>>;
+
+fn fingerprint(paths: &[PathBuf]) -> Fingerprint {
+    paths
+        .iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(path).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect()
+}
+
+/// Resolve once any of `paths` changes (including being created or removed)
+/// and then stays unchanged for the debounce period
+pub async fn wait_for_change(paths: &[PathBuf]) {
+    let mut last = fingerprint(paths);
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let current = fingerprint(paths);
+        if current == last {
+            continue;
+        }
+        last = current;
+
+        // Keep waiting while the files are still being written
+        loop {
+            tokio::time::sleep(DEBOUNCE).await;
+            let settled = fingerprint(paths);
+            if settled == last {
+                return;
+            }
+            last = settled;
+        }
+    }
+}
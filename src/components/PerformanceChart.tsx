@@ -11,7 +11,7 @@ import {
 } from "recharts";
 import { ClockIcon } from "@heroicons/react/20/solid";
 import type { ExecutionInstance } from "../lib/types";
-import { formatDurationForChart } from "../lib/utils";
+import { formatBytes, formatDurationForChart } from "../lib/utils";
 
 interface Props {
   executions: ExecutionInstance[];
@@ -22,11 +22,13 @@ interface ChartData {
   partOne: number | null;
   partTwo: number | null;
   total: number | null;
+  peakMemory: number | null;
+  cpuTime: number | null;
 }
 
 interface TooltipProps {
   active?: boolean;
-  payload?: Array<{ name: string; value: number; color: string }>;
+  payload?: Array<{ name: string; value: number; color: string; payload: ChartData }>;
   label?: string;
 }
 
@@ -48,6 +50,22 @@ function CustomTooltip({ active, payload, label }: TooltipProps) {
             </span>
           </div>
         ))}
+        {payload[0].payload.cpuTime !== null && (
+          <div className="flex items-center justify-between gap-4 text-sm mt-2">
+            <span className="text-[var(--color-text-muted)]">CPU time</span>
+            <span className="font-mono text-[var(--color-text-secondary)]">
+              {formatDurationForChart(payload[0].payload.cpuTime)}
+            </span>
+          </div>
+        )}
+        {payload[0].payload.peakMemory !== null && (
+          <div className="flex items-center justify-between gap-4 text-sm">
+            <span className="text-[var(--color-text-muted)]">Peak memory</span>
+            <span className="font-mono text-[var(--color-text-secondary)]">
+              {formatBytes(payload[0].payload.peakMemory)}
+            </span>
+          </div>
+        )}
       </div>
     );
   }
@@ -71,8 +89,13 @@ export function PerformanceChart({ executions }: Props) {
     );
 
     const data: ChartData[] = completedExecutions.map((execution) => {
-      const { reindeer, result } = execution;
+      const { reindeer, result, usage } = execution;
       const name = `${reindeer.name} ${reindeer.version}`;
+      const peakMemory = usage?.peak_rss_bytes ?? null;
+      const cpuTime =
+        usage?.user_cpu_ms != null && usage.system_cpu_ms != null
+          ? usage.user_cpu_ms + usage.system_cpu_ms
+          : null;
 
       if (result?.type === "solution") {
         const partOne = result.part_one.duration_ms;
@@ -80,17 +103,19 @@ export function PerformanceChart({ executions }: Props) {
         const total =
           partOne !== null && partTwo !== null ? partOne + partTwo : null;
 
-        return { name, partOne, partTwo, total };
+        return { name, partOne, partTwo, total, peakMemory, cpuTime };
       } else if (result?.type === "script") {
         return {
           name,
           partOne: null,
           partTwo: null,
           total: result.duration_ms,
+          peakMemory,
+          cpuTime,
         };
       }
 
-      return { name, partOne: null, partTwo: null, total: null };
+      return { name, partOne: null, partTwo: null, total: null, peakMemory, cpuTime };
     });
 
     // Check if we have any actual duration data
@@ -0,0 +1,320 @@
+// This file is generated by `make bindings` from the Rust types in src-tauri/src.
+// Do not edit it by hand.
+
+export const PROTOCOL_VERSION = 2;
+
+/** An event sent on an execution's channel while a reindeer is running */
+export type ExecutionEvent = { execution_id: string; protocol_version: number } & (
+  | { data: QueuePosition; event_type: "queued" }
+  | { data: unknown; event_type: "initial" }
+  | { data: unknown; event_type: "patch" }
+  | { data: ConsoleFrame; event_type: "console" }
+  | { data: StderrOutput; event_type: "stderr" }
+  | { data: ConsoleTruncated; event_type: "truncated" }
+  | { data: BenchmarkSummary; event_type: "benchmark" }
+  | { data: ResourceSample; event_type: "metrics" }
+  | { data: Completion; event_type: "complete" }
+  | { data: ExecutionError; event_type: "error" }
+);
+
+/** An event emitted by an interactive REPL session */
+export type ReplEvent = { protocol_version: number; session_id: string } & (
+  | { data: ReplOutput; event_type: "stdout" }
+  | { data: ReplOutput; event_type: "stderr" }
+  | { data: ReplClosed; event_type: "closed" }
+);
+
+/** The same solution run on several reindeer, with their answers compared */
+export interface MatrixResult {
+  matrix_id: string;
+  part_one: PartConsensus;
+  part_two: PartConsensus;
+  runs: MatrixRun[];
+}
+
+/** A completed execution, stored as one line of the history file */
+export interface HistoryRecord {
+  aoc_day: number | null;
+  aoc_year: number | null;
+  exit_code: number;
+  file_path: string | null;
+  id: string;
+  mode: string;
+  part_one_ms: number | null;
+  part_two_ms: number | null;
+  reindeer_codename: string;
+  reindeer_version: string;
+  result: unknown;
+  source_hash: string;
+  /** Milliseconds since the Unix epoch */
+  timestamp: number;
+}
+
+/** Criteria for querying history; unset fields match every record */
+export interface HistoryFilter {
+  aoc_day: number | null;
+  aoc_year: number | null;
+  file_path: string | null;
+  /** Only return the most recent N records */
+  limit: number | null;
+  reindeer_codename: string | null;
+}
+
+/** Optional per-call settings for `run_execution` */
+export interface ExecutionOptions {
+  /** Saved path of the solution, used to group execution history and to find the `.input` file saved next to it */
+  file_path: string | null;
+  /** Run even if the result cache holds a result for this run */
+  force: boolean;
+  /** Puzzle input to run against, replacing any `.input` file next to the solution */
+  input: ExecutionInput | null;
+  /** How `input` reaches the reindeer */
+  input_delivery: InputDelivery;
+  /** Number of measured runs in benchmark mode */
+  iterations: number | null;
+  /** Overrides for the limits configured in `Settings` */
+  limits: LimitOverrides | null;
+  /** Record every event of the run, with timings, to this file for replay */
+  record_path: string | null;
+  /** Editor tab the execution belongs to, so watch re-runs can supersede it */
+  tab_id: string | null;
+  /** In test modes, run only these tests (0-based positions of the `test:` blocks in the source) */
+  test_indices: number[] | null;
+  /** Number of unmeasured runs before measuring in benchmark mode */
+  warmups: number | null;
+}
+
+/** The authoritative result of an execution, built by applying the reindeer's RFC 6902 patches to its initial state */
+export interface ExecutionSnapshot {
+  /** Set if the execution was stopped before it finished */
+  cancelled: CancelReason | null;
+  complete: boolean;
+  /** Wall-clock time of the whole execution, once complete */
+  duration_ms: number | null;
+  execution_id: string;
+  exit_code: number | null;
+  /** Resource limit the reindeer was stopped for */
+  limit: LimitExceeded | null;
+  mode: string;
+  patches_applied: number;
+  /** The reindeer the execution ran on */
+  reindeer: Reindeer | null;
+  /** Current `SolutionExecutionState`/`TestExecutionState`/`ScriptExecutionState` */
+  result: unknown;
+  /** Signal that terminated the reindeer (Unix only) */
+  signal: number | null;
+}
+
+/** User preferences, saved in the app's config */
+export interface Settings {
+  aoc_session_token: string | null;
+  console_limits: ConsoleLimits;
+  debug_mode: boolean;
+  default_reindeer: string | null;
+  /** Run queued executions strictly one at a time for reliable timings */
+  exclusive_benchmarking: boolean;
+  execution_limits: ExecutionLimits;
+  format_on_save: boolean;
+  formatter_path: string | null;
+  /** Maximum executions running at once (defaults to the number of cores) */
+  max_parallel_executions: number | null;
+  /** Interval for sampling the memory and CPU use of running reindeer (sampling is off when unset) */
+  metrics_interval_ms: number | null;
+  /** Answer unchanged runs from the result cache instead of running them */
+  result_cache: boolean;
+  theme: string;
+}
+
+/** Emitted before a watch-triggered execution starts, so the frontend can create its execution entry ahead of the execution events */
+export interface WatchRun {
+  execution_id: string;
+  impl_id: string;
+  mode: string;
+  tab_id: string;
+}
+
+/** Standard formats test results can be exported in for CI report viewers */
+export type ReportFormat = "junit" | "tap";
+
+export interface BenchmarkSummary {
+  iterations: number;
+  part_one: PartStats | null;
+  part_two: PartStats | null;
+  warmups: number;
+}
+
+/** Why a running reindeer was stopped before it finished */
+export type CancelReason =
+  | "user"
+  | "app_exit"
+  | "superseded";
+
+export interface Completion {
+  /** The result was replayed from the result cache rather than run, with the timings and usage of the original run */
+  cached: boolean;
+  cancelled: CancelReason | null;
+  /** Full command line, only reported in debug mode */
+  command: string | null;
+  /** Wall-clock time of the whole execution */
+  duration_ms: number;
+  /** Process exit code, or -1 if it was terminated by a signal */
+  exit_code: number;
+  limit: LimitExceeded | null;
+  /** Signal that terminated the reindeer (Unix only) */
+  signal: number | null;
+  /** What the reindeer used, from its final run in benchmark mode */
+  usage: ResourceUsage | null;
+}
+
+/** Lines printed by the solution via puts(), batched so output-heavy solutions do not flood the frontend with one event per line */
+export interface ConsoleFrame {
+  lines: string[];
+}
+
+/** Caps on console output forwarded to the frontend per run (unset means unlimited). Output past either cap is written to a log file instead. */
+export interface ConsoleLimits {
+  max_bytes: number | null;
+  max_lines: number | null;
+}
+
+/** Console output hit the configured limits, so the rest is written to a log */
+export interface ConsoleTruncated {
+  /** Log holding the complete output, unless it could not be created */
+  log_path: string | null;
+}
+
+export interface ExecutionError {
+  location: Location | null;
+  message: string;
+}
+
+/** Puzzle input supplied explicitly for an execution, instead of relying on the reindeer finding a `.input` file next to the solution */
+export type ExecutionInput =
+  | { content: string; type: "text" }
+  | { path: string; type: "file" }
+  | { day: number; type: "aoc"; year: number };
+
+/** Resource limits applied to each reindeer run (unset means unlimited) */
+export interface ExecutionLimits {
+  cpu_time_secs: number | null;
+  max_memory_mb: number | null;
+  timeout_secs: number | null;
+}
+
+/** How resolved input reaches the reindeer */
+export type InputDelivery =
+  | "sibling_file"
+  | "stdin";
+
+/** The resource limit that caused a reindeer run to be stopped */
+export type LimitExceeded = "timeout" | "cpu_time" | "memory";
+
+/** A limit to use in place of the configured one, or none at all */
+export type LimitOverride = number | NoLimit;
+
+/** Per-call changes to the configured `ExecutionLimits` (unset keeps the configured limit) */
+export interface LimitOverrides {
+  cpu_time_secs: LimitOverride | null;
+  max_memory_mb: LimitOverride | null;
+  timeout_secs: LimitOverride | null;
+}
+
+/** A position in the solution source */
+export interface Location {
+  column: number;
+  line: number;
+}
+
+/** One reindeer's answer to a part */
+export interface MatrixAnswer {
+  /** Time the reindeer reported for solving the part */
+  duration_ms: number | null;
+  impl_id: string;
+  /** None if the reindeer did not produce an answer */
+  value: string | null;
+}
+
+/** How one reindeer's run of a matrix ended */
+export interface MatrixRun {
+  /** Wall-clock time of the run, excluding time spent queued */
+  duration_ms: number | null;
+  /** Why the run could not be started or completed */
+  error: string | null;
+  execution_id: string;
+  /** None if the reindeer could not be started */
+  exit_code: number | null;
+  impl_id: string;
+}
+
+/** The `"unlimited"` override, removing a configured limit */
+export type NoLimit = "unlimited";
+
+/** Every reindeer's answer to a part */
+export interface PartConsensus {
+  /** In the order the reindeer were requested */
+  answers: MatrixAnswer[];
+  /** Set when the answers differ, including when only some reindeer produced one */
+  disagreement: boolean;
+}
+
+/** Summary statistics for one part across all measured runs */
+export interface PartStats {
+  mean: number;
+  median: number;
+  min: number;
+  p95: number;
+  samples: number;
+  stddev: number;
+}
+
+/** Where a waiting execution sits in the scheduler queue */
+export interface QueuePosition {
+  /** 1-based; 1 means it starts next */
+  position: number;
+}
+
+export interface Reindeer {
+  codename: string;
+  id: string;
+  name: string;
+  path: string;
+  version: string;
+}
+
+export interface ReplClosed {
+  /** Process exit code, or -1 if it was terminated by a signal */
+  exit_code: number;
+}
+
+/** Output from the REPL as it arrives, not split into lines, so prompts without a trailing newline are shown straight away */
+export interface ReplOutput {
+  text: string;
+}
+
+/** Live resource usage of a running reindeer and any processes it spawned (Linux only) */
+export interface ResourceSample {
+  /** CPU use since the previous sample, where 100 is one full core */
+  cpu_percent: number;
+  /** Time since sampling started */
+  elapsed_ms: number;
+  processes: number;
+  rss_bytes: number;
+}
+
+/** Resources consumed by a single reindeer run */
+export interface ResourceUsage {
+  /** Peak resident set size (Unix only) */
+  peak_rss_bytes: number | null;
+  /** CPU time spent in the kernel (Unix only) */
+  system_cpu_ms: number | null;
+  /** CPU time spent in user mode (Unix only) */
+  user_cpu_ms: number | null;
+  /** Time from spawning the reindeer until it exited */
+  wall_time_ms: number;
+}
+
+/** A line written by the reindeer to stderr */
+export interface StderrOutput {
+  location: Location | null;
+  message: string;
+}
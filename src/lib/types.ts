@@ -1,18 +1,12 @@
-export interface Reindeer {
-  id: string;
-  name: string;
-  codename: string;
-  version: string;
-  path: string;
-}
-
-export interface Settings {
-  aoc_session_token: string | null;
-  default_reindeer: string | null;
-  theme: string;
-  format_on_save: boolean;
-  debug_mode: boolean;
-}
+import type {
+  BenchmarkSummary,
+  ExecutionSnapshot as ExecutionSnapshotBinding,
+  HistoryRecord as HistoryRecordBinding,
+  Reindeer,
+  ResourceSample,
+  ResourceUsage,
+  WatchRun as WatchRunBinding,
+} from "./bindings";
 
 export interface FormatterStatus {
   installed: boolean;
@@ -47,14 +41,47 @@ export interface Asset {
   size: number;
 }
 
-export interface ExecutionEvent {
-  execution_id: string;
-  event_type: "initial" | "patch" | "console" | "complete" | "error";
-  data: unknown;
+export type {
+  BenchmarkSummary,
+  CancelReason,
+  Completion,
+  ConsoleLimits,
+  ExecutionEvent,
+  ExecutionInput,
+  ExecutionLimits,
+  ExecutionOptions,
+  HistoryFilter,
+  InputDelivery,
+  LimitExceeded,
+  MatrixAnswer,
+  MatrixResult,
+  MatrixRun,
+  PartConsensus,
+  PartStats as BenchmarkPartStats,
+  Reindeer,
+  ReplEvent,
+  ReportFormat,
+  ResourceSample,
+  ResourceUsage,
+  Settings,
+} from "./bindings";
+
+// One chunk of a REPL session's transcript, as typed or as received
+export interface ReplTranscriptEntry {
+  kind: "input" | "stdout" | "stderr";
+  text: string;
+}
+
+export interface ReplSessionState {
+  id: string;
+  reindeerId: string;
+  transcript: ReplTranscriptEntry[];
+  closed: boolean;
+  exitCode: number | null;
 }
 
 export type ExecutionStatus = "idle" | "running" | "complete" | "error";
-export type ExecutionMode = "run" | "test" | "test-slow" | "script";
+export type ExecutionMode = "run" | "test" | "test-slow" | "benchmark" | "script";
 
 export interface PartResult {
   status: "pending" | "running" | "complete";
@@ -105,6 +132,17 @@ export type ExecutionState =
   | ScriptExecutionState
   | null;
 
+// The bindings only know the mode and result as a string and arbitrary JSON
+export type ExecutionSnapshot = Omit<ExecutionSnapshotBinding, "mode" | "result"> & {
+  mode: ExecutionMode;
+  result: ExecutionState;
+};
+
+// Narrowed like ExecutionSnapshot
+export type WatchRun = Omit<WatchRunBinding, "mode"> & {
+  mode: ExecutionMode;
+};
+
 export interface TabFile {
   id: string;
   name: string;
@@ -124,6 +162,13 @@ export interface ExecutionInstance {
   exitCode: number | null;
   startedAt: number;
   command: string | null;
+  benchmark: BenchmarkSummary | null;
+  usage: ResourceUsage | null;
+  // Live samples while running, oldest first
+  metrics: ResourceSample[];
+  // Full console log, once output exceeded the console limits
+  consoleLogPath: string | null;
+  queuePosition: number | null;
 }
 
 export interface AocPuzzle {
@@ -133,3 +178,9 @@ export interface AocPuzzle {
   description_html: string;
   input: string | null;
 }
+
+// Narrowed like ExecutionSnapshot
+export type HistoryRecord = Omit<HistoryRecordBinding, "mode" | "result"> & {
+  mode: ExecutionMode;
+  result: ExecutionState;
+};
@@ -2,10 +2,11 @@ import { configureStore } from "@reduxjs/toolkit";
 import { useDispatch, useSelector } from "react-redux";
 import tabsReducer from "./slices/tabsSlice";
 import reindeerReducer from "./slices/reindeerSlice";
-import executionReducer from "./slices/executionSlice";
+import executionReducer, { snapshotCleanup } from "./slices/executionSlice";
 import settingsReducer from "./slices/settingsSlice";
 import formatterReducer from "./slices/formatterSlice";
 import aocReducer from "./slices/aocSlice";
+import replReducer from "./slices/replSlice";
 
 export const store = configureStore({
   reducer: {
@@ -15,7 +16,9 @@ export const store = configureStore({
     settings: settingsReducer,
     formatter: formatterReducer,
     aoc: aocReducer,
+    repl: replReducer,
   },
+  middleware: (getDefaultMiddleware) => getDefaultMiddleware().prepend(snapshotCleanup.middleware),
 });
 
 export type RootState = ReturnType<typeof store.getState>;
@@ -1,15 +1,26 @@
-import { createSlice, createAsyncThunk } from "@reduxjs/toolkit";
+import { createSlice, createAsyncThunk, createListenerMiddleware, isAnyOf } from "@reduxjs/toolkit";
 import type { PayloadAction } from "@reduxjs/toolkit";
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { applyPatch, type Operation } from "fast-json-patch";
 import type {
+  BenchmarkSummary,
   ExecutionState,
   ExecutionMode,
   ExecutionEvent,
+  ExecutionInput,
   ExecutionInstance,
+  ExecutionOptions,
+  InputDelivery,
+  LimitExceeded,
+  MatrixResult,
   Reindeer,
+  ReportFormat,
+  ResourceSample,
+  ResourceUsage,
+  WatchRun,
 } from "../../lib/types";
+import { PROTOCOL_VERSION } from "../../lib/bindings";
 import type { AppDispatch, RootState } from "..";
 import { closeTab } from "./tabsSlice";
 
@@ -18,6 +29,8 @@ interface ExecutionSliceState {
   activeExecutionId: string | null;
   multiSelectMode: boolean;
   selectedReindeerIds: string[];
+  // Answer comparisons of matrix runs, by matrix ID
+  matrixResults: Record<string, MatrixResult>;
 }
 
 const initialState: ExecutionSliceState = {
@@ -25,51 +38,99 @@ const initialState: ExecutionSliceState = {
   activeExecutionId: null,
   multiSelectMode: false,
   selectedReindeerIds: [],
+  matrixResults: {},
 };
 
-// Global listener for all execution events
-let globalUnlistenFn: UnlistenFn | null = null;
+// Live samples kept per execution, enough for several minutes at 1s intervals
+const MAX_METRICS_SAMPLES = 600;
 
-// Set up global event listener that routes events by execution_id
-const setupGlobalListener = async (dispatch: AppDispatch) => {
-  if (globalUnlistenFn) return;
+const LIMIT_MESSAGES: Record<LimitExceeded, string> = {
+  timeout: "wall-clock timeout exceeded",
+  cpu_time: "CPU time limit exceeded",
+  memory: "memory limit exceeded",
+};
+
+// Create the channel an execution's events are delivered on. A channel may
+// carry several executions (e.g. every re-run of a watch), so events are still
+// routed by execution_id.
+const createEventChannel = (dispatch: AppDispatch) => {
+  const channel = new Channel<ExecutionEvent>();
+  channel.onmessage = (payload) => {
+    const executionId = payload.execution_id;
 
-  globalUnlistenFn = await listen<ExecutionEvent>("execution-event", (event) => {
-    const { execution_id, event_type, data } = event.payload;
+    if (payload.protocol_version !== PROTOCOL_VERSION) {
+      console.warn(`Unsupported execution protocol version ${payload.protocol_version}`);
+    }
 
-    switch (event_type) {
+    switch (payload.event_type) {
+      case "queued":
+        dispatch(setQueuePosition({ executionId, position: payload.data.position }));
+        break;
       case "initial":
-        dispatch(setInitialResult({ executionId: execution_id, result: data as ExecutionState }));
+        dispatch(setInitialResult({ executionId, result: payload.data as ExecutionState }));
         break;
       case "patch":
-        dispatch(applyResultPatch({ executionId: execution_id, patches: data as Operation[] }));
+        dispatch(applyResultPatch({ executionId, patches: payload.data as Operation[] }));
         break;
       case "console":
-        dispatch(appendConsole({ executionId: execution_id, message: (data as { message: string }).message }));
+        dispatch(appendConsoleLines({ executionId, lines: payload.data.lines }));
+        break;
+      case "stderr":
+        dispatch(appendConsole({ executionId, message: payload.data.message }));
+        break;
+      case "truncated":
+        dispatch(setConsoleTruncated({ executionId, logPath: payload.data.log_path }));
+        break;
+      case "benchmark":
+        dispatch(setBenchmarkSummary({ executionId, summary: payload.data }));
+        break;
+      case "metrics":
+        dispatch(appendMetrics({ executionId, sample: payload.data }));
         break;
       case "complete": {
-        const completeData = data as { exit_code: number; command?: string | null };
-        dispatch(completeExecution({
-          executionId: execution_id,
-          exitCode: completeData.exit_code,
-          command: completeData.command,
-        }));
+        const { cancelled, limit, exit_code, command, usage, cached } = payload.data;
+        if (cached) {
+          dispatch(appendConsole({ executionId, message: "Result from cache, unchanged since the last run" }));
+        } else if (cancelled) {
+          dispatch(appendConsole({ executionId, message: `Cancelled (${cancelled})` }));
+        } else if (limit) {
+          dispatch(appendConsole({ executionId, message: `Stopped: ${LIMIT_MESSAGES[limit]}` }));
+        }
+        dispatch(completeExecution({ executionId, exitCode: exit_code, command, usage }));
         break;
       }
       case "error":
-        dispatch(setError({ executionId: execution_id, message: (data as { message: string }).message }));
+        dispatch(setError({ executionId, message: payload.data.message }));
         break;
     }
-  });
+  };
+  return channel;
 };
 
 export const startExecution = createAsyncThunk<
   void,
-  { implId: string; source: string; mode: ExecutionMode; workingDir?: string; tabId: string },
+  {
+    implId: string;
+    source: string;
+    mode: ExecutionMode;
+    workingDir?: string;
+    tabId: string;
+    input?: ExecutionInput;
+    inputDelivery?: InputDelivery;
+    // 0-based positions of the test: blocks to run in test modes
+    testIndices?: number[];
+    // Record the run's events to this .santa-run.jsonl file for replay
+    recordPath?: string;
+    // Run even when the result cache holds this run's result
+    force?: boolean;
+  },
   { dispatch: AppDispatch; state: RootState }
 >(
   "execution/start",
-  async ({ implId, source, mode, workingDir, tabId }, { dispatch, getState }) => {
+  async (
+    { implId, source, mode, workingDir, tabId, input, inputDelivery, testIndices, recordPath, force },
+    { dispatch, getState }
+  ) => {
     // Get the reindeer object
     const state = getState();
     const reindeer = state.reindeer.reindeer.find(r => r.id === implId);
@@ -80,9 +141,6 @@ export const startExecution = createAsyncThunk<
     // Generate unique execution ID
     const executionId = `${implId}_${Date.now()}`;
 
-    // Set up global event listener if not already set up
-    await setupGlobalListener(dispatch);
-
     // Create the execution entry BEFORE calling invoke
     // This is critical because events will arrive during the invoke call
     dispatch(createExecution({
@@ -92,32 +150,188 @@ export const startExecution = createAsyncThunk<
       tabId,
     }));
 
-    // Start the execution - events will be handled by the listener
+    // Start the execution - events arrive on its own channel
+    const filePath = state.tabs.tabs.find(t => t.id === tabId)?.path ?? null;
     await invoke("run_execution", {
       executionId,
       implId,
       source,
       mode,
       workingDir,
+      onEvent: createEventChannel(dispatch),
+      options: {
+        file_path: filePath,
+        tab_id: tabId,
+        input: input ?? null,
+        input_delivery: inputDelivery ?? "sibling_file",
+        test_indices: testIndices ?? null,
+        record_path: recordPath ?? null,
+        force: force ?? false,
+      } satisfies Partial<ExecutionOptions>,
+    });
+  }
+);
+
+// Listener for executions the backend starts on its own when a watched file changes
+let watchUnlistenFn: UnlistenFn | null = null;
+
+export const startWatch = createAsyncThunk<
+  void,
+  {
+    reindeerIds: string[];
+    path: string;
+    mode: ExecutionMode;
+    tabId: string;
+    // Kept for every re-run of the watch
+    input?: ExecutionInput;
+    inputDelivery?: InputDelivery;
+    testIndices?: number[];
+  },
+  { dispatch: AppDispatch; state: RootState }
+>(
+  "execution/startWatch",
+  async ({ reindeerIds, path, mode, tabId, input, inputDelivery, testIndices }, { dispatch, getState }) => {
+    if (!watchUnlistenFn) {
+      watchUnlistenFn = await listen<WatchRun>("watch-run", (event) => {
+        const { tab_id, execution_id, impl_id, mode } = event.payload;
+        const reindeer = getState().reindeer.reindeer.find(r => r.id === impl_id);
+        if (!reindeer) return;
+        dispatch(createExecution({ executionId: execution_id, reindeer, mode, tabId: tab_id }));
+      });
+    }
+
+    await invoke("start_watch", {
+      tabId,
+      path,
+      implIds: reindeerIds,
+      mode,
+      onEvent: createEventChannel(dispatch),
+      options: {
+        input: input ?? null,
+        input_delivery: inputDelivery ?? "sibling_file",
+        test_indices: testIndices ?? null,
+      } satisfies Partial<ExecutionOptions>,
+    });
+  }
+);
+
+export const stopWatch = createAsyncThunk(
+  "execution/stopWatch",
+  async (tabId: string) => {
+    await invoke("stop_watch", { tabId });
+  }
+);
+
+// Extension of recorded runs, e.g. day01.santa-run.jsonl
+export const RECORDING_EXTENSION = "santa-run.jsonl";
+
+export const replayRecording = createAsyncThunk<
+  void,
+  { path: string; mode: ExecutionMode; tabId: string; speed?: number },
+  { dispatch: AppDispatch }
+>(
+  "execution/replay",
+  async ({ path, mode, tabId, speed }, { dispatch }) => {
+    const executionId = `replay_${Date.now()}`;
+
+    // Replays have no reindeer behind them, so label the panel with the file
+    const name = path.split(/[\\/]/).pop() ?? path;
+    dispatch(createExecution({
+      executionId,
+      reindeer: { id: "replay", name: "Replay", codename: "replay", version: name, path },
+      mode,
+      tabId,
+    }));
+
+    await invoke("replay_recording", {
+      path,
+      executionId,
+      speed,
+      onEvent: createEventChannel(dispatch),
     });
   }
 );
 
 export const startMultiExecution = createAsyncThunk<
   void,
-  { reindeerIds: string[]; source: string; mode: ExecutionMode; workingDir?: string; tabId: string },
+  {
+    reindeerIds: string[];
+    source: string;
+    mode: ExecutionMode;
+    workingDir?: string;
+    tabId: string;
+    input?: ExecutionInput;
+    inputDelivery?: InputDelivery;
+    testIndices?: number[];
+  },
   { dispatch: AppDispatch }
 >(
   "execution/startMulti",
-  async ({ reindeerIds, source, mode, workingDir, tabId }, { dispatch }) => {
+  async (
+    { reindeerIds, source, mode, workingDir, tabId, input, inputDelivery, testIndices },
+    { dispatch }
+  ) => {
     // Dispatch startExecution for each reindeer in parallel
     const promises = reindeerIds.map(implId =>
-      dispatch(startExecution({ implId, source, mode, workingDir, tabId }))
+      dispatch(startExecution({ implId, source, mode, workingDir, tabId, input, inputDelivery, testIndices }))
     );
     await Promise.all(promises);
   }
 );
 
+// Run the solution on every reindeer and compare their answers. Each run still
+// gets its own execution entry, fed from the shared channel as it progresses.
+export const runMatrix = createAsyncThunk<
+  MatrixResult,
+  {
+    reindeerIds: string[];
+    source: string;
+    workingDir?: string;
+    tabId: string;
+    input?: ExecutionInput;
+    inputDelivery?: InputDelivery;
+  },
+  { dispatch: AppDispatch; state: RootState }
+>(
+  "execution/runMatrix",
+  async ({ reindeerIds, source, workingDir, tabId, input, inputDelivery }, { dispatch, getState }) => {
+    const state = getState();
+    const matrixId = `matrix_${Date.now()}`;
+
+    // The backend runs each reindeer as execution `${matrixId}_${implId}`
+    for (const implId of reindeerIds) {
+      const reindeer = state.reindeer.reindeer.find(r => r.id === implId);
+      if (!reindeer) {
+        throw new Error("Reindeer not found");
+      }
+      dispatch(createExecution({ executionId: `${matrixId}_${implId}`, reindeer, mode: "run", tabId }));
+    }
+
+    const filePath = state.tabs.tabs.find(t => t.id === tabId)?.path ?? null;
+    return await invoke<MatrixResult>("run_matrix", {
+      matrixId,
+      implIds: reindeerIds,
+      source,
+      workingDir,
+      onEvent: createEventChannel(dispatch),
+      options: {
+        file_path: filePath,
+        tab_id: tabId,
+        input: input ?? null,
+        input_delivery: inputDelivery ?? "sibling_file",
+      } satisfies Partial<ExecutionOptions>,
+    });
+  }
+);
+
+// Write a finished test execution's results as JUnit XML or TAP for CI viewers
+export const exportTestReport = createAsyncThunk(
+  "execution/exportTestReport",
+  async ({ executionId, format, path }: { executionId: string; format: ReportFormat; path: string }) => {
+    await invoke("export_test_report", { executionId, format, path });
+  }
+);
+
 export const cancelExecution = createAsyncThunk<
   string,
   string,
@@ -210,16 +424,28 @@ export const executionSlice = createSlice({
         exitCode: null,
         startedAt: Date.now(),
         command: null,
+        benchmark: null,
+        usage: null,
+        metrics: [],
+        consoleLogPath: null,
+        queuePosition: null,
       };
       // Set as active if we don't have one or if in single mode
       if (!state.activeExecutionId || !state.multiSelectMode) {
         state.activeExecutionId = executionId;
       }
     },
+    setQueuePosition: (state, action: PayloadAction<{ executionId: string; position: number }>) => {
+      const { executionId, position } = action.payload;
+      const execution = state.executions[executionId];
+      if (!execution) return;
+      execution.queuePosition = position;
+    },
     setInitialResult: (state, action: PayloadAction<{ executionId: string; result: ExecutionState }>) => {
       const { executionId, result } = action.payload;
       const execution = state.executions[executionId];
       if (!execution) return;
+      execution.queuePosition = null;
 
       // Normalize script output format from CLI
       const payload = result as unknown as Record<string, unknown>;
@@ -251,19 +477,56 @@ export const executionSlice = createSlice({
         // Ignore patch errors
       }
     },
+    appendConsoleLines: (state, action: PayloadAction<{ executionId: string; lines: string[] }>) => {
+      const { executionId, lines } = action.payload;
+      const execution = state.executions[executionId];
+      if (!execution) return;
+      execution.consoleOutput.push(...lines);
+    },
     appendConsole: (state, action: PayloadAction<{ executionId: string; message: string }>) => {
       const { executionId, message } = action.payload;
       const execution = state.executions[executionId];
       if (!execution) return;
       execution.consoleOutput.push(message);
     },
-    completeExecution: (state, action: PayloadAction<{ executionId: string; exitCode: number; command?: string | null }>) => {
-      const { executionId, exitCode, command } = action.payload;
+    setConsoleTruncated: (state, action: PayloadAction<{ executionId: string; logPath: string | null }>) => {
+      const { executionId, logPath } = action.payload;
       const execution = state.executions[executionId];
       if (!execution) return;
+      execution.consoleLogPath = logPath;
+      execution.consoleOutput.push(
+        logPath
+          ? `Output truncated, full log written to ${logPath}`
+          : "Output truncated, the full log could not be written"
+      );
+    },
+    setBenchmarkSummary: (state, action: PayloadAction<{ executionId: string; summary: BenchmarkSummary }>) => {
+      const { executionId, summary } = action.payload;
+      const execution = state.executions[executionId];
+      if (!execution) return;
+      execution.benchmark = summary;
+    },
+    appendMetrics: (state, action: PayloadAction<{ executionId: string; sample: ResourceSample }>) => {
+      const { executionId, sample } = action.payload;
+      const execution = state.executions[executionId];
+      if (!execution) return;
+      execution.metrics.push(sample);
+      if (execution.metrics.length > MAX_METRICS_SAMPLES) {
+        execution.metrics.shift();
+      }
+    },
+    completeExecution: (
+      state,
+      action: PayloadAction<{ executionId: string; exitCode: number; command?: string | null; usage?: ResourceUsage | null }>
+    ) => {
+      const { executionId, exitCode, command, usage } = action.payload;
+      const execution = state.executions[executionId];
+      if (!execution) return;
+      execution.queuePosition = null;
       execution.status = exitCode === 0 ? "complete" : "error";
       execution.exitCode = exitCode;
       execution.command = command ?? null;
+      execution.usage = usage ?? null;
     },
     setError: (state, action: PayloadAction<{ executionId: string; message: string }>) => {
       const { executionId, message } = action.payload;
@@ -287,6 +550,9 @@ export const executionSlice = createSlice({
           failedExecution.consoleOutput.push(`Error: ${action.error.message}`);
         }
       })
+      .addCase(runMatrix.fulfilled, (state, action) => {
+        state.matrixResults[action.payload.matrix_id] = action.payload;
+      })
       .addCase(cancelExecution.fulfilled, (state, action) => {
         const execution = state.executions[action.payload];
         if (execution) {
@@ -319,11 +585,33 @@ export const {
   clearAllExecutions,
   clearExecutionsForTab,
   createExecution,
+  setQueuePosition,
   setInitialResult,
   applyResultPatch,
   appendConsole,
+  appendConsoleLines,
+  setConsoleTruncated,
+  setBenchmarkSummary,
+  appendMetrics,
   completeExecution,
   setError,
 } = executionSlice.actions;
 
+// The backend keeps a snapshot of every execution for test report export, so
+// discard it once the execution is removed from the panel
+export const snapshotCleanup = createListenerMiddleware();
+
+snapshotCleanup.startListening({
+  matcher: isAnyOf(removeExecution, clearAllExecutions, clearExecutionsForTab, closeTab),
+  effect: (_action, api) => {
+    const before = (api.getOriginalState() as RootState).execution.executions;
+    const after = (api.getState() as RootState).execution.executions;
+    for (const executionId of Object.keys(before)) {
+      if (!(executionId in after)) {
+        void invoke("discard_execution_snapshot", { executionId });
+      }
+    }
+  },
+});
+
 export default executionSlice.reducer;
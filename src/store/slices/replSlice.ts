@@ -0,0 +1,123 @@
+import { createSlice, createAsyncThunk } from "@reduxjs/toolkit";
+import type { PayloadAction } from "@reduxjs/toolkit";
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { ReplEvent, ReplSessionState, ReplTranscriptEntry } from "../../lib/types";
+import { PROTOCOL_VERSION } from "../../lib/bindings";
+import type { AppDispatch } from "..";
+
+interface ReplState {
+  sessions: Record<string, ReplSessionState>;
+}
+
+const initialState: ReplState = {
+  sessions: {},
+};
+
+// Global listener for all REPL events
+let globalUnlistenFn: UnlistenFn | null = null;
+
+const setupGlobalListener = async (dispatch: AppDispatch) => {
+  if (globalUnlistenFn) return;
+
+  globalUnlistenFn = await listen<ReplEvent>("repl-event", (event) => {
+    const payload = event.payload;
+    const sessionId = payload.session_id;
+
+    if (payload.protocol_version !== PROTOCOL_VERSION) {
+      console.warn(`Unsupported REPL protocol version ${payload.protocol_version}`);
+    }
+
+    switch (payload.event_type) {
+      case "stdout":
+      case "stderr":
+        dispatch(appendTranscript({
+          sessionId,
+          entry: { kind: payload.event_type, text: payload.data.text },
+        }));
+        break;
+      case "closed":
+        dispatch(sessionClosed({ sessionId, exitCode: payload.data.exit_code }));
+        break;
+    }
+  });
+};
+
+export const startReplSession = createAsyncThunk<
+  ReplSessionState,
+  { implId: string; workingDir?: string },
+  { dispatch: AppDispatch }
+>(
+  "repl/start",
+  async ({ implId, workingDir }, { dispatch }) => {
+    await setupGlobalListener(dispatch);
+    const sessionId = await invoke<string>("start_repl_session", { implId, workingDir });
+    return { id: sessionId, reindeerId: implId, transcript: [], closed: false, exitCode: null };
+  }
+);
+
+export const sendReplInput = createAsyncThunk(
+  "repl/send",
+  async ({ sessionId, input }: { sessionId: string; input: string }) => {
+    await invoke("send_repl_input", { sessionId, input });
+  }
+);
+
+export const closeReplSession = createAsyncThunk(
+  "repl/close",
+  async (sessionId: string) => {
+    await invoke("close_repl_session", { sessionId });
+  }
+);
+
+export const replSlice = createSlice({
+  name: "repl",
+  initialState,
+  reducers: {
+    appendTranscript: (state, action: PayloadAction<{ sessionId: string; entry: ReplTranscriptEntry }>) => {
+      const { sessionId, entry } = action.payload;
+      // Output such as the REPL's banner can arrive before start resolves
+      const session = state.sessions[sessionId] ??= {
+        id: sessionId,
+        reindeerId: "",
+        transcript: [],
+        closed: false,
+        exitCode: null,
+      };
+      session.transcript.push(entry);
+    },
+    sessionClosed: (state, action: PayloadAction<{ sessionId: string; exitCode: number }>) => {
+      const { sessionId, exitCode } = action.payload;
+      const session = state.sessions[sessionId];
+      if (!session) return;
+      session.closed = true;
+      session.exitCode = exitCode;
+    },
+    removeSession: (state, action: PayloadAction<string>) => {
+      delete state.sessions[action.payload];
+    },
+  },
+  extraReducers: (builder) => {
+    builder
+      .addCase(startReplSession.fulfilled, (state, action) => {
+        const early = state.sessions[action.payload.id];
+        state.sessions[action.payload.id] = {
+          ...action.payload,
+          transcript: early?.transcript ?? [],
+        };
+      })
+      // Echo input into the transcript as soon as it is sent
+      .addCase(sendReplInput.pending, (state, action) => {
+        const { sessionId, input } = action.meta.arg;
+        state.sessions[sessionId]?.transcript.push({ kind: "input", text: input });
+      });
+  },
+});
+
+export const {
+  appendTranscript,
+  sessionClosed,
+  removeSession,
+} = replSlice.actions;
+
+export default replSlice.reducer;
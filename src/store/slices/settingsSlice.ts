@@ -15,7 +15,21 @@ const initialState: SettingsState = {
     default_reindeer: null,
     theme: "dark",
     format_on_save: false,
+    formatter_path: null,
     debug_mode: false,
+    execution_limits: {
+      timeout_secs: null,
+      cpu_time_secs: null,
+      max_memory_mb: null,
+    },
+    max_parallel_executions: null,
+    console_limits: {
+      max_lines: 10000,
+      max_bytes: 1024 * 1024,
+    },
+    exclusive_benchmarking: false,
+    metrics_interval_ms: null,
+    result_cache: false,
   },
   isLoading: false,
   isModalOpen: false,